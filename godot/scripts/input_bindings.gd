@@ -0,0 +1 @@
+extends InputBindingsBase
@@ -1,9 +1,12 @@
 use std::collections::VecDeque;
 
-use godot::engine::{Node, ResourceLoader};
+use godot::engine::utilities::linear_to_db;
+use godot::engine::{AudioEffectLowPassFilter, AudioServer, Node, ResourceLoader};
 use godot::prelude::*;
 use rand::Rng;
 
+use crate::settings::SettingsBase;
+
 #[derive(GodotClass)]
 #[class(base=Node)]
 pub struct AudioBase {
@@ -11,6 +14,7 @@ pub struct AudioBase {
     bus: &'static str,
     available: VecDeque<Gd<AudioStreamPlayer>>,
     queue: VecDeque<String>,
+    underwater_effect: i32,
 
     base: Base<Node>,
 }
@@ -20,15 +24,19 @@ impl INode for AudioBase {
     fn init(base: Base<Node>) -> Self {
         Self {
             num_players: 12,
-            bus: "master",
+            bus: "SFX",
             available: VecDeque::new(),
             queue: VecDeque::new(),
+            underwater_effect: -1,
 
             base,
         }
     }
 
     fn ready(&mut self) {
+        Self::ensure_bus(self.bus);
+        Self::ensure_bus("Music");
+
         for _ in 0..self.num_players {
             let mut p = AudioStreamPlayer::new_alloc();
             self.base_mut().add_child(p.clone().upcast());
@@ -45,6 +53,28 @@ impl INode for AudioBase {
 
             self.available.push_back(p);
         }
+
+        let mut server = AudioServer::singleton();
+        let bus_index = server.get_bus_index(self.bus.into());
+        if bus_index >= 0 {
+            let mut effect = AudioEffectLowPassFilter::new_gd();
+            effect.set_cutoff(600.0);
+            server.add_bus_effect(bus_index, effect.upcast());
+            self.underwater_effect = server.get_bus_effect_count(bus_index) - 1;
+            server.set_bus_effect_enabled(bus_index, self.underwater_effect, false);
+        } else {
+            godot_error!(
+                "Audio bus {} is missing, underwater muffling is disabled",
+                self.bus
+            );
+        }
+
+        let mut settings = self.base().get_node_as::<SettingsBase>("/root/Settings");
+        settings.connect(
+            "settings_changed".into(),
+            self.base().callable("_on_settings_changed"),
+        );
+        Self::apply_volumes(&settings.bind());
     }
 
     fn process(&mut self, _delta: f64) {
@@ -73,6 +103,51 @@ impl AudioBase {
         ));
     }
 
+    #[func]
+    pub fn set_underwater(&mut self, underwater: bool) {
+        let mut server = AudioServer::singleton();
+        let bus_index = server.get_bus_index(self.bus.into());
+        if bus_index < 0 || self.underwater_effect < 0 {
+            return;
+        }
+        server.set_bus_effect_enabled(bus_index, self.underwater_effect, underwater);
+    }
+
+    #[func]
+    fn _on_settings_changed(&mut self) {
+        let settings = self.base().get_node_as::<SettingsBase>("/root/Settings");
+        Self::apply_volumes(&settings.bind());
+    }
+
+    fn apply_volumes(settings: &SettingsBase) {
+        let mut server = AudioServer::singleton();
+        for (bus, volume) in [
+            ("Master", settings.master_volume()),
+            ("SFX", settings.sfx_volume()),
+            ("Music", settings.music_volume()),
+        ] {
+            let bus_index = server.get_bus_index(bus.into());
+            if bus_index >= 0 {
+                // A slider at zero would otherwise ask for -inf dB
+                server.set_bus_mute(bus_index, volume <= 0.0);
+                let volume_db = linear_to_db(volume as f64).max(-80.0);
+                server.set_bus_volume_db(bus_index, volume_db as f32);
+            }
+        }
+    }
+
+    fn ensure_bus(name: &str) {
+        let mut server = AudioServer::singleton();
+        if server.get_bus_index(name.into()) >= 0 {
+            return;
+        }
+
+        let bus_index = server.get_bus_count();
+        server.add_bus();
+        server.set_bus_name(bus_index, name.into());
+        server.set_bus_send(bus_index, "Master".into());
+    }
+
     #[func]
     fn _on_stream_finished(&mut self, stream: Gd<AudioStreamPlayer>) {
         self.available.push_back(stream);
@@ -0,0 +1,77 @@
+use godot::engine::{Area3D, CharacterBody3D, Engine, IArea3D, ImmediateMesh, Node3D};
+use godot::prelude::*;
+
+use crate::jump_pad::{create_trajectory, update_trajectory};
+
+#[derive(GodotClass)]
+#[class(tool, base=Area3D)]
+pub struct BoostZone {
+    #[export]
+    impulse: Vector3,
+
+    #[export]
+    sound_boost: GString,
+
+    trajectory: Option<Gd<ImmediateMesh>>,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for BoostZone {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            impulse: Vector3::new(0.0, 4.0, -12.0),
+
+            sound_boost: GString::from("sounds/jump_b.ogg"),
+
+            trajectory: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        if Engine::singleton().is_editor_hint() {
+            let trajectory = create_trajectory(&mut self.base_mut());
+            self.trajectory = Some(trajectory);
+            return;
+        }
+
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if let Some(trajectory) = self.trajectory.as_mut() {
+            let transform = self.base().get_global_transform();
+            update_trajectory(trajectory, transform.origin, transform.basis * self.impulse);
+        }
+    }
+}
+
+#[godot_api]
+impl BoostZone {
+    #[func]
+    fn _on_body_entered(&mut self, body: Gd<Node3D>) {
+        let Ok(mut body) = body.try_cast::<CharacterBody3D>() else {
+            return;
+        };
+        if !body.has_method("launch".into()) {
+            return;
+        }
+
+        // Unlike a JumpPad, a boost keeps the momentum the body already has
+        let velocity =
+            body.get_velocity() + self.base().get_global_transform().basis * self.impulse;
+        body.call("launch".into(), &[Variant::from(velocity)]);
+
+        let sound_boost = self.sound_boost.to_string();
+        self.play_sound(&sound_boost);
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+}
@@ -0,0 +1,85 @@
+use godot::engine::{FastNoiseLite, INode};
+use godot::prelude::*;
+use rand::Rng;
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct CameraShake {
+    #[export(range = (0.0, 2.0))]
+    shake_scale: f32,
+    #[export(range = (0.0, 5.0))]
+    decay: f32,
+    #[export(range = (0.0, 1.0))]
+    max_offset: f32,
+    #[export]
+    max_rotation: Vector3,
+    #[export(range = (1.0, 100.0))]
+    frequency: f32,
+
+    camera: OnReady<Gd<Camera3D>>,
+    pivot: OnReady<Gd<Node3D>>,
+
+    noise: Gd<FastNoiseLite>,
+    trauma: f32,
+    time: f32,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for CameraShake {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            shake_scale: 1.0,
+            decay: 1.5,
+            max_offset: 0.1,
+            max_rotation: Vector3::new(0.05, 0.05, 0.1),
+            frequency: 20.0,
+
+            camera: OnReady::manual(),
+            pivot: OnReady::manual(),
+
+            noise: FastNoiseLite::new_gd(),
+            trauma: 0.0,
+            time: 0.0,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let camera = self.base().get_parent().unwrap().cast::<Camera3D>();
+        self.pivot.init(camera.get_parent_node_3d().unwrap());
+        self.camera.init(camera);
+
+        self.noise.set_seed(rand::thread_rng().gen());
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.time += delta as f32 * self.frequency;
+        self.trauma = (self.trauma - self.decay * delta as f32).max(0.0);
+
+        // Squaring trauma keeps small hits subtle while big ones still read
+        let shake = self.trauma * self.trauma * self.shake_scale;
+
+        let rotation = Vector3::new(
+            self.max_rotation.x * shake * self.noise.get_noise_2d(self.time, 0.0),
+            self.max_rotation.y * shake * self.noise.get_noise_2d(self.time, 100.0),
+            self.max_rotation.z * shake * self.noise.get_noise_2d(self.time, 200.0),
+        );
+        self.pivot.set_rotation(rotation);
+
+        let h_offset = self.max_offset * shake * self.noise.get_noise_2d(self.time, 300.0);
+        let v_offset = self.max_offset * shake * self.noise.get_noise_2d(self.time, 400.0);
+        self.camera.set_h_offset(h_offset);
+        self.camera.set_v_offset(v_offset);
+    }
+}
+
+#[godot_api]
+impl CameraShake {
+    #[func]
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
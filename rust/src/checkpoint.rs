@@ -0,0 +1,50 @@
+use godot::engine::{Area3D, IArea3D, Node3D};
+use godot::prelude::*;
+
+use crate::player::Player;
+
+#[derive(GodotClass)]
+#[class(base=Area3D)]
+pub struct Checkpoint {
+    reached: bool,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for Checkpoint {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            reached: false,
+
+            base,
+        }
+    }
+
+    fn enter_tree(&mut self) {
+        self.base_mut().add_to_group("checkpoint".into());
+    }
+
+    fn ready(&mut self) {
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+    }
+}
+
+#[godot_api]
+impl Checkpoint {
+    #[func]
+    fn _on_body_entered(&mut self, body: Gd<Node3D>) {
+        if self.reached || body.try_cast::<Player>().is_err() {
+            return;
+        }
+
+        self.reached = true;
+        let transform = self.base().get_global_transform();
+        self.base_mut()
+            .emit_signal("activated".into(), &[Variant::from(transform)]);
+    }
+
+    #[signal]
+    fn activated(transform: Transform3D);
+}
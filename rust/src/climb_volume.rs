@@ -0,0 +1,46 @@
+use godot::engine::{Area3D, IArea3D, Node3D};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Area3D)]
+pub struct ClimbVolume {
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for ClimbVolume {
+    fn init(base: Base<Area3D>) -> Self {
+        Self { base }
+    }
+
+    fn ready(&mut self) {
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+        let callable = self.base().callable("_on_body_exited");
+        self.base_mut().connect("body_exited".into(), callable);
+    }
+}
+
+#[godot_api]
+impl ClimbVolume {
+    #[func]
+    fn _on_body_entered(&mut self, mut body: Gd<Node3D>) {
+        if body.has_method("enter_climb".into()) {
+            // The climbable face points along the volume's local +Z
+            let normal = self
+                .base()
+                .get_global_transform()
+                .basis
+                .col_c()
+                .normalized();
+            body.call("enter_climb".into(), &[Variant::from(normal)]);
+        }
+    }
+
+    #[func]
+    fn _on_body_exited(&mut self, mut body: Gd<Node3D>) {
+        if body.has_method("exit_climb".into()) {
+            body.call("exit_climb".into(), &[]);
+        }
+    }
+}
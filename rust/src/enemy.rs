@@ -1,21 +1,50 @@
-use godot::engine::{AnimatedSprite3D, Area3D, Node3D, RayCast3D};
+use godot::engine::{AnimatedSprite3D, Area3D, CollisionObject3D, Node3D, RayCast3D, Timer};
 use godot::prelude::*;
 use rand::Rng;
 
+use crate::enemy_archetype::EnemyArchetype;
+use crate::enemy_common::{self, Awareness, EnemyStats, HitReaction};
+use crate::enemy_state::{EnemyState, Senses};
+use crate::weapon::Weapon;
+
 #[derive(GodotClass)]
 #[class(base=Area3D)]
 struct Enemy {
     #[export]
     player: Option<Gd<Node3D>>,
 
+    #[export]
+    archetype: Option<Gd<EnemyArchetype>>,
+    #[export]
+    patrol_points: PackedVector3Array,
+
+    stats: OnReady<EnemyStats>,
+
     raycast: OnReady<Gd<RayCast3D>>,
+    sight: OnReady<Gd<RayCast3D>>,
+    whisker: OnReady<Gd<RayCast3D>>,
     muzzle_a: OnReady<Gd<AnimatedSprite3D>>,
     muzzle_b: OnReady<Gd<AnimatedSprite3D>>,
+    reaction: OnReady<HitReaction>,
 
     health: f32,
     time: f64,
     target_position: Vector3,
     destroyed: bool,
+    death_time: f64,
+    spin_axis: Vector3,
+
+    awareness: Awareness,
+    origin: Vector3,
+    patrol_index: usize,
+
+    velocity: Vector3,
+    strafe_direction: f32,
+    strafe_time: f64,
+
+    squad_flank: Vector3,
+    squad_steer: Vector3,
+    can_fire: bool,
 
     base: Base<Area3D>,
 }
@@ -26,38 +55,147 @@ impl INode3D for Enemy {
         Self {
             player: None,
 
+            archetype: None,
+            patrol_points: PackedVector3Array::new(),
+
+            stats: OnReady::manual(),
+
             raycast: OnReady::manual(),
+            sight: OnReady::manual(),
+            whisker: OnReady::manual(),
             muzzle_a: OnReady::manual(),
             muzzle_b: OnReady::manual(),
+            reaction: OnReady::manual(),
 
             health: 100.0,
             time: 0.0,
             target_position: Vector3::new(0.0, 0.0, 0.0),
             destroyed: false,
+            death_time: 0.0,
+            spin_axis: Vector3::UP,
+
+            awareness: Awareness::default(),
+            origin: Vector3::ZERO,
+            patrol_index: 0,
+
+            velocity: Vector3::ZERO,
+            strafe_direction: 1.0,
+            strafe_time: 0.0,
+
+            squad_flank: Vector3::ZERO,
+            squad_steer: Vector3::ZERO,
+            can_fire: true,
 
             base,
         }
     }
 
+    fn enter_tree(&mut self) {
+        self.base_mut().add_to_group("enemy".into());
+    }
+
     fn ready(&mut self) {
         let position = self.base().get_position();
         self.target_position = position;
+        self.origin = position;
 
         self.raycast.init(self.base().get_node_as("RayCast"));
         self.muzzle_a.init(self.base().get_node_as("MuzzleA"));
         self.muzzle_b.init(self.base().get_node_as("MuzzleB"));
+
+        self.stats.init(EnemyStats::load(self.archetype.clone()));
+        self.health = self.stats.max_health;
+        let mut enemy = self.base().clone().upcast::<Node3D>();
+        let model = enemy_common::equip(&mut enemy, &self.stats, &mut self.raycast);
+        self.reaction.init(HitReaction::new(model));
+
+        // Separate from the attack raycast, which keeps its fixed reach
+        let sight = RayCast3D::new_alloc();
+        self.base_mut().add_child(sight.clone().upcast());
+        self.sight.init(sight);
+
+        // Whiskers feel for level geometry only, never the player
+        let mut whisker = RayCast3D::new_alloc();
+        if let Some(player) = self
+            .player
+            .clone()
+            .and_then(|x| x.try_cast::<CollisionObject3D>().ok())
+        {
+            whisker.add_exception(player);
+        }
+        self.base_mut().add_child(whisker.clone().upcast());
+        self.whisker.init(whisker);
+
+        if let Some(mut player) = self.player.clone() {
+            if player.has_signal("noise_made".into()) {
+                player.connect("noise_made".into(), self.base().callable("_on_noise_made"));
+            }
+        }
     }
 
     fn process(&mut self, delta: f64) {
-        let position = self.player.as_ref().map(|x| x.get_position());
-        if let Some(position) = position {
-            //godot_print!("position: {:?}", position);
-            self.base_mut()
-                .look_at_ex(position + Vector3::new(0.0, 0.5, 0.0))
-                .use_model_front(true)
-                .done();
+        if self.destroyed {
+            self.fall(delta);
+            return;
+        }
+
+        if self.reaction.update(delta, &self.stats) {
+            self.base().get_node_as::<Timer>("Timer").start();
+        }
+        if self.reaction.is_staggered() {
+            // Reeling, the knockback bleeds off and nothing else happens
+            self.fly(self.target_position, Vector3::ZERO, delta);
+            let target_position = self.target_position;
+            self.base_mut().set_position(target_position);
+            return;
         }
-        self.target_position.y += ((self.time * 5.0).cos() * delta) as f32;
+
+        let senses = self.sense(delta);
+        if let Some(previous) = self
+            .awareness
+            .advance(&senses, &self.stats.thresholds(), delta)
+        {
+            let state = self.awareness.state;
+            enemy_common::emit_state_changed(&mut self.base().clone().upcast(), previous, state);
+        }
+
+        let player_position = self.player.as_ref().map(|x| x.get_position());
+        let mut goal = self.target_position;
+        let mut strafe = Vector3::ZERO;
+        match self.awareness.state {
+            EnemyState::Idle => {}
+            EnemyState::Patrol => goal = self.patrol(),
+            EnemyState::Alert => {
+                if let Some(position) = player_position {
+                    self.face(position + Vector3::new(0.0, 0.5, 0.0));
+                }
+            }
+            EnemyState::Chase | EnemyState::Attack => {
+                if let Some(position) = player_position {
+                    self.face(position + Vector3::new(0.0, 0.5, 0.0));
+                    goal = self.engagement_position(position);
+
+                    if self.awareness.state == EnemyState::Attack {
+                        strafe = self.strafe(position, delta);
+                    }
+                }
+            }
+            EnemyState::Investigate => goal = self.investigate(),
+            EnemyState::Flee => {
+                if let Some(position) = player_position {
+                    let mut away = self.target_position - position;
+                    away.y = 0.0;
+                    goal = self.target_position + away.normalized() * 5.0;
+                    self.face(goal);
+                }
+            }
+        }
+
+        self.fly(goal, strafe, delta);
+
+        let frequency = self.stats.bob_frequency as f64;
+        self.target_position.y +=
+            ((self.time * frequency).cos() * frequency * delta) as f32 * self.stats.bob_height;
 
         self.time += delta;
 
@@ -70,36 +208,325 @@ impl INode3D for Enemy {
 impl Enemy {
     #[func]
     fn damage(&mut self, amount: f32) {
-        self.play_sound("sounds/enemy_hurt.ogg");
-        self.health -= amount;
-        if self.health < 0.0 {
-            self.destroy();
+        self.hurt(amount, None, None);
+    }
+
+    // Like `damage`, but credits whoever fired, and with what, should the
+    // hit be fatal
+    #[func]
+    fn damage_by(&mut self, amount: f32, source: Gd<Node3D>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
+        // Shoved away from the shooter
+        if let Some(weapon) = weapon.as_ref() {
+            let knockback = weapon.get("knockback".into()).to::<f32>() * self.stats.knockback_scale;
+            let direction = self.base().get_global_position() - source.get_global_position();
+            if direction.length() > 0.01 {
+                self.velocity += direction.normalized() * knockback;
+            }
         }
+
+        self.hurt(amount, Some(source), weapon);
     }
 
     #[func]
     fn destroy(&mut self) {
-        self.play_sound("sounds/enemy_destroy.ogg");
+        self.destroy_by(None, None);
+    }
+
+    #[func]
+    fn get_state(&self) -> GString {
+        GString::from(self.awareness.state.name())
+    }
+
+    fn hurt(&mut self, amount: f32, killer: Option<Gd<Node3D>>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
+        // Getting shot gives away roughly where the shooter is
+        if let Some(position) = self.player.as_ref().map(|x| x.get_position()) {
+            self.awareness.notice(position);
+        }
+
+        let sound_hurt = self.stats.sound_hurt.to_string();
+        self.play_sound(&sound_hurt);
+
+        if self.reaction.hit(amount, &self.stats) {
+            self.base().get_node_as::<Timer>("Timer").stop();
+        }
+
+        self.health -= amount;
+        if self.health < 0.0 {
+            self.destroy_by(killer, weapon);
+        }
+    }
+
+    // Drops out of the sky spinning and bursts on impact or once
+    // `death_delay` runs out
+    fn destroy_by(&mut self, killer: Option<Gd<Node3D>>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
         self.destroyed = true;
-        self.base_mut().queue_free();
+        self.base_mut().remove_from_group("enemy".into());
+        self.reaction.clear();
+        self.base().get_node_as::<Timer>("Timer").stop();
+
+        let mut rng = rand::thread_rng();
+        let axis = Vector3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        self.spin_axis = if axis.length() > 0.01 {
+            axis.normalized()
+        } else {
+            Vector3::UP
+        };
+        self.velocity.y = self.velocity.y.max(0.0) + 2.0;
+
+        enemy_common::emit_destroyed(&mut self.base().clone().upcast(), killer, weapon);
+    }
+
+    // Set every frame by an EnemySquad, when there is one. `flank` is the
+    // direction from the player to hold, or zero to pick one freely.
+    #[func]
+    fn set_squad_orders(&mut self, flank: Vector3, steer: Vector3, can_fire: bool) {
+        self.squad_flank = flank;
+        self.squad_steer = steer;
+        self.can_fire = can_fire;
+    }
+
+    #[func]
+    fn _on_noise_made(&mut self, position: Vector3, radius: f32) {
+        if self.destroyed {
+            return;
+        }
+
+        let hearing = self.stats.hearing;
+        self.awareness
+            .hear(position, radius, hearing, self.target_position);
     }
 
     #[func]
     fn _on_timer_timeout(&mut self) {
-        self.raycast.force_raycast_update();
+        if self.destroyed || !self.can_fire || self.awareness.state != EnemyState::Attack {
+            return;
+        }
+
+        let shooter = self.base().clone().upcast::<Node3D>();
+        if self.stats.projectile.is_none() {
+            if enemy_common::fire_hitscan(&shooter, &self.stats, &mut self.raycast) {
+                Self::play_default_animation(&mut self.muzzle_a);
+                Self::play_default_animation(&mut self.muzzle_b);
+            }
+            return;
+        }
 
-        if self.raycast.is_colliding() {
-            let collider = self.raycast.get_collider();
+        let Some(player) = self.player.clone() else {
+            return;
+        };
+        let muzzle = if rand::thread_rng().gen_bool(0.5) {
+            &mut self.muzzle_a
+        } else {
+            &mut self.muzzle_b
+        };
+        Self::play_default_animation(muzzle);
+        let origin = muzzle.get_global_position();
+        enemy_common::fire_projectile(&shooter, &self.stats, &player, origin);
+    }
 
-            if let Some(mut collider) = collider {
-                if collider.has_method("damage".into()) {
-                    Self::play_default_animation(&mut self.muzzle_a);
-                    Self::play_default_animation(&mut self.muzzle_b);
-                    self.play_sound("sounds/enemy_attack.ogg");
-                    collider.call("damage".into(), &[Variant::from(5.0)]);
-                }
+    fn sense(&mut self, delta: f64) -> Senses {
+        let forward = self.base().get_global_transform().basis.col_c();
+        let engaged = self.awareness.state.is_engaged();
+        let can_see = self.player.as_ref().is_some_and(|player| {
+            enemy_common::can_see_player(player, &mut self.sight, forward, &self.stats, engaged)
+        });
+        let player_position = self.player.as_ref().map(|x| x.get_position());
+
+        Senses {
+            health: self.health / self.stats.max_health.max(1.0),
+            can_patrol: !self.patrol_points.is_empty(),
+            ..self
+                .awareness
+                .sense(delta, self.target_position, player_position, can_see, 0.5)
+        }
+    }
+
+    fn investigate(&mut self) -> Vector3 {
+        let Some(position) = self.awareness.last_known_position else {
+            return self.target_position;
+        };
+
+        let destination = position + Vector3::new(0.0, 1.5, 0.0);
+        self.face(Vector3::new(
+            destination.x,
+            self.target_position.y,
+            destination.z,
+        ));
+        destination
+    }
+
+    fn patrol(&mut self) -> Vector3 {
+        let waypoint = enemy_common::patrol(
+            self.origin,
+            &self.patrol_points,
+            &mut self.patrol_index,
+            self.target_position,
+        );
+
+        self.face(Vector3::new(waypoint.x, self.target_position.y, waypoint.z));
+        waypoint
+    }
+
+    // Hovers above the player at the preferred distance, on the flank the
+    // squad assigned or else the side the enemy is already on
+    fn engagement_position(&self, player_position: Vector3) -> Vector3 {
+        let mut away = self.target_position - player_position;
+        away.y = 0.0;
+        if self.squad_flank != Vector3::ZERO {
+            away = self.squad_flank;
+        } else if away.length() < 0.01 {
+            away = Vector3::BACK;
+        }
+
+        player_position
+            + away.normalized() * self.stats.engagement_distance
+            + Vector3::new(0.0, 1.5, 0.0)
+    }
+
+    // Circles around the player, switching direction every few seconds
+    fn strafe(&mut self, player_position: Vector3, delta: f64) -> Vector3 {
+        self.strafe_time -= delta;
+        if self.strafe_time <= 0.0 {
+            self.strafe_time = rand::thread_rng().gen_range(1.5..3.5);
+            self.strafe_direction = -self.strafe_direction;
+        }
+
+        let mut away = self.target_position - player_position;
+        away.y = 0.0;
+        if away.length() < 0.01 {
+            return Vector3::ZERO;
+        }
+
+        Vector3::UP.cross(away).normalized() * self.strafe_direction * self.stats.strafe_speed
+    }
+
+    fn fly(&mut self, goal: Vector3, strafe: Vector3, delta: f64) {
+        // Slows down on arrival rather than overshooting the goal
+        let mut desired =
+            ((goal - self.target_position) * 2.0).limit_length(Some(self.stats.move_speed));
+        desired += strafe;
+        desired += self.squad_steer;
+        desired += self.avoidance(desired);
+
+        self.velocity = self
+            .velocity
+            .move_toward(desired, self.stats.acceleration * delta as f32);
+        self.target_position += self.velocity * delta as f32;
+    }
+
+    // Casts a handful of whiskers along the heading and steers away from any
+    // level geometry they touch, harder the closer it is
+    fn avoidance(&mut self, heading: Vector3) -> Vector3 {
+        let heading = if self.velocity.length() > 0.1 {
+            self.velocity
+        } else {
+            heading
+        };
+        if heading.length() < 0.01 || self.stats.avoidance_distance <= 0.0 {
+            return Vector3::ZERO;
+        }
+
+        let forward = heading.normalized();
+        let mut side = forward.cross(Vector3::UP);
+        if side.length() < 0.01 {
+            side = Vector3::RIGHT;
+        }
+        let side = side.normalized();
+        let up = side.cross(forward);
+
+        let origin = self.base().get_global_position();
+        let mut push = Vector3::ZERO;
+        for direction in [
+            forward,
+            (forward + side * 0.5).normalized(),
+            (forward - side * 0.5).normalized(),
+            (forward + up * 0.5).normalized(),
+            (forward - up * 0.5).normalized(),
+        ] {
+            let local = self
+                .whisker
+                .to_local(origin + direction * self.stats.avoidance_distance);
+            self.whisker.set_target_position(local);
+            self.whisker.force_raycast_update();
+
+            if self.whisker.is_colliding() {
+                let distance = self.whisker.get_collision_point().distance_to(origin);
+                let weight = 1.0 - distance / self.stats.avoidance_distance;
+                push += self.whisker.get_collision_normal() * weight;
             }
         }
+
+        push * self.stats.move_speed * self.stats.avoidance_strength
+    }
+
+    fn fall(&mut self, delta: f64) {
+        self.death_time += delta;
+        self.velocity.y -= 20.0 * delta as f32;
+        let step = self.velocity * delta as f32;
+
+        // Feels ahead along the fall for anything to crash into
+        let origin = self.base().get_global_position();
+        let reach = step + (step + Vector3::DOWN * 0.001).normalized() * 0.3;
+        let local = self.whisker.to_local(origin + reach);
+        self.whisker.set_target_position(local);
+        self.whisker.force_raycast_update();
+
+        if self.whisker.is_colliding() || self.death_time >= self.stats.death_delay {
+            self.explode();
+            return;
+        }
+
+        self.target_position += step;
+        let target_position = self.target_position;
+        self.base_mut().set_position(target_position);
+
+        let (axis, angle) = (self.spin_axis, self.stats.death_spin * delta as f32);
+        self.base_mut().rotate(axis, angle);
+    }
+
+    // Loot lands on whatever is below rather than hanging in the air
+    fn explode(&mut self) {
+        let position = self.base().get_global_position();
+
+        let local = self.whisker.to_local(position + Vector3::DOWN * 20.0);
+        self.whisker.set_target_position(local);
+        self.whisker.force_raycast_update();
+        let ground = if self.whisker.is_colliding() {
+            self.whisker.get_collision_point() + Vector3::new(0.0, 0.5, 0.0)
+        } else {
+            position
+        };
+
+        let mut enemy = self.base().clone().upcast::<Node3D>();
+        let player = self.player.as_ref();
+        enemy_common::explode(&mut enemy, &self.stats, player, position, ground);
+    }
+
+    fn face(&mut self, position: Vector3) {
+        if position.distance_to(self.base().get_position()) < 0.01 {
+            return;
+        }
+
+        self.base_mut()
+            .look_at_ex(position)
+            .use_model_front(true)
+            .done();
     }
 
     fn play_default_animation(animation: &mut Gd<AnimatedSprite3D>) {
@@ -114,4 +541,10 @@ impl Enemy {
         let mut audio = self.base().get_node_as::<Node>("/root/Audio");
         audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
     }
+
+    #[signal]
+    fn state_changed(from: GString, to: GString);
+
+    #[signal]
+    fn enemy_destroyed(enemy: Gd<Node3D>, killer: Gd<Node3D>, weapon: Gd<Weapon>);
 }
@@ -0,0 +1,125 @@
+use godot::engine::Resource;
+use godot::prelude::*;
+
+use crate::loot_drop::LootDrop;
+use crate::weapon::Weapon;
+
+#[derive(GodotClass)]
+#[class(base=Resource)]
+pub struct EnemyArchetype {
+    #[export]
+    model: Option<Gd<PackedScene>>,
+    #[export]
+    max_health: f32,
+
+    #[export]
+    weapon: Option<Gd<Weapon>>,
+    #[export]
+    projectile: Option<Gd<PackedScene>>,
+    #[export(range = (0.0, 30.0))]
+    accuracy_error: f32,
+
+    // Damage soaked up in quick succession before the enemy staggers,
+    // zero never staggers
+    #[export(range = (0.0, 500.0))]
+    poise: f32,
+    #[export(range = (0.0, 5.0))]
+    stagger_time: f64,
+    #[export(range = (0.0, 2.0))]
+    knockback_scale: f32,
+
+    #[export(range = (0.0, 100.0))]
+    detection_radius: f32,
+    #[export(range = (0.0, 360.0))]
+    vision_angle: f32,
+    #[export(range = (0.0, 4.0))]
+    hearing: f32,
+    #[export(range = (0.0, 100.0))]
+    attack_radius: f32,
+    #[export(range = (0.0, 30.0))]
+    lose_sight_timeout: f64,
+    #[export(range = (0.0, 5.0))]
+    alert_time: f64,
+    #[export(range = (0.0, 1.0))]
+    flee_health: f32,
+    #[export(range = (0.0, 30.0))]
+    idle_time: f64,
+
+    #[export(range = (0.0, 20.0))]
+    move_speed: f32,
+    #[export(range = (0.0, 50.0))]
+    acceleration: f32,
+    #[export(range = (0.0, 50.0))]
+    engagement_distance: f32,
+    #[export(range = (0.0, 10.0))]
+    strafe_speed: f32,
+    #[export(range = (0.0, 10.0))]
+    avoidance_distance: f32,
+    #[export(range = (0.0, 5.0))]
+    avoidance_strength: f32,
+
+    #[export(range = (0.0, 20.0))]
+    bob_frequency: f32,
+    #[export(range = (0.0, 1.0))]
+    bob_height: f32,
+
+    #[export]
+    loot: Array<Gd<LootDrop>>,
+    #[export(range = (0.0, 5.0))]
+    death_delay: f64,
+    #[export(range = (0.0, 30.0))]
+    death_spin: f32,
+
+    #[export]
+    sound_hurt: GString,
+    #[export]
+    sound_destroy: GString,
+
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl IResource for EnemyArchetype {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            model: None,
+            max_health: 100.0,
+
+            weapon: None,
+            projectile: None,
+            accuracy_error: 4.0,
+
+            poise: 40.0,
+            stagger_time: 0.8,
+            knockback_scale: 0.2,
+
+            detection_radius: 12.0,
+            vision_angle: 120.0,
+            hearing: 1.0,
+            attack_radius: 8.0,
+            lose_sight_timeout: 3.0,
+            alert_time: 0.5,
+            flee_health: 0.2,
+            idle_time: 2.0,
+
+            move_speed: 3.0,
+            acceleration: 6.0,
+            engagement_distance: 6.0,
+            strafe_speed: 2.0,
+            avoidance_distance: 2.5,
+            avoidance_strength: 1.5,
+
+            bob_frequency: 5.0,
+            bob_height: 0.2,
+
+            loot: Array::new(),
+            death_delay: 1.2,
+            death_spin: 8.0,
+
+            sound_hurt: GString::from("sounds/enemy_hurt.ogg"),
+            sound_destroy: GString::from("sounds/enemy_destroy.ogg"),
+
+            base,
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use godot::engine::base_material_3d::{ShadingMode, Transparency};
+use godot::engine::utilities::deg_to_rad;
+use godot::engine::{GeometryInstance3D, Node3D, RayCast3D, StandardMaterial3D, Timer};
+use godot::prelude::*;
+
+use crate::enemy_archetype::EnemyArchetype;
+use crate::enemy_state::{next_state, EnemyState, Senses, Thresholds};
+use crate::impact::Impact;
+use crate::loot_drop::{self, LootDrop};
+use crate::projectile;
+use crate::weapon::Weapon;
+
+// What `Enemy` and `GroundEnemy` have in common: the stats copied out of
+// their archetype, what they know about the player, how they react to hits,
+// how they fire and how they burst apart
+
+const FLINCH_TIME: f64 = 0.15;
+
+pub struct EnemyStats {
+    pub model: Option<Gd<PackedScene>>,
+    pub max_health: f32,
+    pub weapon: Option<Gd<Weapon>>,
+    pub projectile: Option<Gd<PackedScene>>,
+    pub accuracy_error: f32,
+
+    pub poise: f32,
+    pub stagger_time: f64,
+    pub knockback_scale: f32,
+
+    pub detection_radius: f32,
+    pub vision_angle: f32,
+    pub hearing: f32,
+    pub attack_radius: f32,
+    pub lose_sight_timeout: f64,
+    pub alert_time: f64,
+    pub flee_health: f32,
+    pub idle_time: f64,
+
+    pub move_speed: f32,
+    pub acceleration: f32,
+    pub engagement_distance: f32,
+    pub strafe_speed: f32,
+    pub avoidance_distance: f32,
+    pub avoidance_strength: f32,
+
+    pub bob_frequency: f32,
+    pub bob_height: f32,
+
+    pub loot: Array<Gd<LootDrop>>,
+    pub death_delay: f64,
+    pub death_spin: f32,
+
+    pub sound_hurt: GString,
+    pub sound_destroy: GString,
+}
+
+impl EnemyStats {
+    // Falls back to the archetype's defaults when none is assigned
+    pub fn load(archetype: Option<Gd<EnemyArchetype>>) -> Self {
+        let archetype = archetype.unwrap_or_else(EnemyArchetype::new_gd);
+        let stat = |name: &str| archetype.get(name.into());
+
+        Self {
+            model: stat("model").try_to().ok(),
+            max_health: stat("max_health").to(),
+            weapon: stat("weapon").try_to().ok(),
+            projectile: stat("projectile").try_to().ok(),
+            accuracy_error: stat("accuracy_error").to(),
+
+            poise: stat("poise").to(),
+            stagger_time: stat("stagger_time").to(),
+            knockback_scale: stat("knockback_scale").to(),
+
+            detection_radius: stat("detection_radius").to(),
+            vision_angle: stat("vision_angle").to(),
+            hearing: stat("hearing").to(),
+            attack_radius: stat("attack_radius").to(),
+            lose_sight_timeout: stat("lose_sight_timeout").to(),
+            alert_time: stat("alert_time").to(),
+            flee_health: stat("flee_health").to(),
+            idle_time: stat("idle_time").to(),
+
+            move_speed: stat("move_speed").to(),
+            acceleration: stat("acceleration").to(),
+            engagement_distance: stat("engagement_distance").to(),
+            strafe_speed: stat("strafe_speed").to(),
+            avoidance_distance: stat("avoidance_distance").to(),
+            avoidance_strength: stat("avoidance_strength").to(),
+
+            bob_frequency: stat("bob_frequency").to(),
+            bob_height: stat("bob_height").to(),
+
+            loot: stat("loot").try_to().unwrap_or_default(),
+            death_delay: stat("death_delay").to(),
+            death_spin: stat("death_spin").to(),
+
+            sound_hurt: stat("sound_hurt").to(),
+            sound_destroy: stat("sound_destroy").to(),
+        }
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            detection_radius: self.detection_radius,
+            attack_radius: self.attack_radius,
+            lose_sight_timeout: self.lose_sight_timeout,
+            alert_time: self.alert_time,
+            flee_health: self.flee_health,
+            idle_time: self.idle_time,
+        }
+    }
+}
+
+// Adds the archetype's model under the enemy's `Model` pivot, which carries
+// any offset the body needs, and fits its Timer and attack raycast to the
+// weapon
+pub fn equip(
+    enemy: &mut Gd<Node3D>,
+    stats: &EnemyStats,
+    raycast: &mut Gd<RayCast3D>,
+) -> Option<Gd<Node3D>> {
+    if let Some(weapon) = stats.weapon.as_ref() {
+        let cooldown = weapon.get("cooldown".into()).to::<f64>();
+        let max_distance = weapon.get("max_distance".into()).to::<f32>();
+
+        let mut timer = enemy.get_node_as::<Timer>("Timer");
+        timer.set_wait_time(cooldown);
+        raycast.set_target_position(Vector3::new(0.0, 0.0, max_distance));
+    }
+
+    let model = stats.model.as_ref()?.instantiate()?;
+    let mut pivot = enemy
+        .try_get_node_as::<Node3D>("Model")
+        .unwrap_or_else(|| enemy.clone());
+    pivot.add_child(model.clone());
+    model.try_cast::<Node3D>().ok()
+}
+
+// What an enemy knows about the player and the state it acts on
+pub struct Awareness {
+    pub state: EnemyState,
+    pub time_in_state: f64,
+    pub time_since_seen: f64,
+    pub heard: bool,
+    pub last_known_position: Option<Vector3>,
+}
+
+impl Default for Awareness {
+    fn default() -> Self {
+        Self {
+            state: EnemyState::Idle,
+            time_in_state: 0.0,
+            time_since_seen: 0.0,
+            heard: false,
+            last_known_position: None,
+        }
+    }
+}
+
+impl Awareness {
+    // Gathers this frame's senses, leaving health and patrolling to the
+    // caller. `reach` is how close counts as having got to the last-known
+    // position.
+    pub fn sense(
+        &mut self,
+        delta: f64,
+        position: Vector3,
+        player_position: Option<Vector3>,
+        can_see: bool,
+        reach: f32,
+    ) -> Senses {
+        let distance = player_position
+            .map(|x| x.distance_to(position))
+            .unwrap_or(f32::INFINITY);
+
+        if can_see {
+            self.last_known_position = player_position;
+        }
+        if can_see || self.heard {
+            self.time_since_seen = 0.0;
+        } else {
+            self.time_since_seen += delta;
+        }
+
+        let reached_lead = self.last_known_position.map_or(true, |x| {
+            Vector2::new(x.x - position.x, x.z - position.z).length() < reach
+        });
+
+        let heard = self.heard;
+        self.heard = false;
+
+        Senses {
+            can_see,
+            heard,
+            reached_lead,
+            distance,
+            time_since_seen: self.time_since_seen,
+            time_in_state: self.time_in_state,
+            health: 1.0,
+            can_patrol: false,
+        }
+    }
+
+    // Returns the state left behind, if any
+    pub fn advance(
+        &mut self,
+        senses: &Senses,
+        thresholds: &Thresholds,
+        delta: f64,
+    ) -> Option<EnemyState> {
+        let previous = self.state;
+        self.state = next_state(previous, senses, thresholds);
+        if self.state != previous {
+            self.time_in_state = 0.0;
+        }
+        self.time_in_state += delta;
+
+        (self.state != previous).then_some(previous)
+    }
+
+    // Sends the enemy to look around `position`
+    pub fn notice(&mut self, position: Vector3) {
+        self.last_known_position = Some(position);
+        self.heard = true;
+    }
+
+    pub fn hear(&mut self, noise: Vector3, radius: f32, hearing: f32, listener: Vector3) {
+        if noise.distance_to(listener) <= radius * hearing {
+            self.notice(noise);
+        }
+    }
+}
+
+// Flashes and rocks the model back briefly after a hit, and staggers the
+// enemy once hits pile up past its poise
+pub struct HitReaction {
+    pub strain: f32,
+    pub flinch: f64,
+    pub stagger: f64,
+
+    model: Option<Gd<Node3D>>,
+    rest_rotation: Vector3,
+    flash: Gd<StandardMaterial3D>,
+}
+
+impl HitReaction {
+    // The flash is overlaid on every mesh of the model, and the flinch is
+    // applied on top of the rotation it was loaded with
+    pub fn new(model: Option<Gd<Node3D>>) -> Self {
+        let mut flash = StandardMaterial3D::new_gd();
+        flash.set_shading_mode(ShadingMode::UNSHADED);
+        flash.set_transparency(Transparency::ALPHA);
+        flash.set_albedo(Color::from_rgba(1.0, 1.0, 1.0, 0.0));
+
+        if let Some(model) = model.as_ref() {
+            for child in model
+                .find_children_ex("*".into())
+                .type_("GeometryInstance3D".into())
+                .done()
+                .iter_shared()
+            {
+                let mut child = child.cast::<GeometryInstance3D>();
+                child.set_material_overlay(flash.clone().upcast());
+            }
+        }
+
+        Self {
+            strain: 0.0,
+            flinch: 0.0,
+            stagger: 0.0,
+
+            rest_rotation: model.as_ref().map_or(Vector3::ZERO, |x| x.get_rotation()),
+            model,
+            flash,
+        }
+    }
+
+    pub fn is_staggered(&self) -> bool {
+        self.stagger > 0.0
+    }
+
+    // Returns whether this hit staggers the enemy
+    pub fn hit(&mut self, amount: f32, stats: &EnemyStats) -> bool {
+        self.flinch = FLINCH_TIME;
+        self.strain += amount;
+        if stats.poise > 0.0 && self.strain >= stats.poise {
+            self.strain = 0.0;
+            self.stagger = stats.stagger_time;
+            return true;
+        }
+        false
+    }
+
+    // Poise slowly recovers and any stagger wears off. Returns whether the
+    // stagger ended this frame.
+    pub fn update(&mut self, delta: f64, stats: &EnemyStats) -> bool {
+        self.flinch = (self.flinch - delta).max(0.0);
+        let flinch = (self.flinch / FLINCH_TIME) as f32;
+        self.flash
+            .set_albedo(Color::from_rgba(1.0, 1.0, 1.0, flinch * 0.8));
+        if let Some(model) = self.model.as_mut() {
+            model.set_rotation(self.rest_rotation + Vector3::new(-flinch * 0.4, 0.0, 0.0));
+        }
+
+        self.strain = (self.strain - stats.poise * 0.5 * delta as f32).max(0.0);
+
+        if self.stagger > 0.0 {
+            self.stagger -= delta;
+            return self.stagger <= 0.0;
+        }
+        false
+    }
+
+    pub fn clear(&mut self) {
+        self.flinch = 0.0;
+        self.stagger = 0.0;
+        self.flash.set_albedo(Color::from_rgba(1.0, 1.0, 1.0, 0.0));
+        if let Some(model) = self.model.as_mut() {
+            model.set_rotation(self.rest_rotation);
+        }
+    }
+}
+
+pub fn emit_state_changed(enemy: &mut Gd<Node3D>, from: EnemyState, to: EnemyState) {
+    enemy.emit_signal(
+        "state_changed".into(),
+        &[
+            Variant::from(GString::from(from.name())),
+            Variant::from(GString::from(to.name())),
+        ],
+    );
+}
+
+// Within range, inside the vision cone and not hidden behind level geometry.
+// Once engaged the enemy keeps facing the player, so the cone only matters
+// for noticing them in the first place.
+pub fn can_see_player(
+    player: &Gd<Node3D>,
+    sight: &mut Gd<RayCast3D>,
+    forward: Vector3,
+    stats: &EnemyStats,
+    engaged: bool,
+) -> bool {
+    let eye = sight.get_global_position();
+    let target = player.get_global_position() + Vector3::new(0.0, 0.5, 0.0);
+    let offset = target - eye;
+    if offset.length() > stats.detection_radius {
+        return false;
+    }
+
+    if !engaged && forward.angle_to(offset) > deg_to_rad(stats.vision_angle as f64 / 2.0) as f32 {
+        return false;
+    }
+
+    let local = sight.to_local(target);
+    sight.set_target_position(local);
+    sight.force_raycast_update();
+
+    sight
+        .get_collider()
+        .is_some_and(|x| x.instance_id() == player.instance_id())
+}
+
+// Moves on to the next patrol point once the current one is reached
+pub fn patrol(
+    origin: Vector3,
+    points: &PackedVector3Array,
+    index: &mut usize,
+    position: Vector3,
+) -> Vector3 {
+    let mut waypoint = origin + points.get(*index);
+    let mut offset = waypoint - position;
+    offset.y = 0.0;
+
+    if offset.length() < 0.5 {
+        *index = (*index + 1) % points.len();
+        waypoint = origin + points.get(*index);
+    }
+
+    waypoint
+}
+
+// Leads the player, who can still sidestep or shoot the projectile down
+pub fn fire_projectile(
+    shooter: &Gd<Node3D>,
+    stats: &EnemyStats,
+    player: &Gd<Node3D>,
+    origin: Vector3,
+) {
+    let (Some(scene), Some(weapon)) = (stats.projectile.as_ref(), stats.weapon.as_ref()) else {
+        return;
+    };
+
+    let target = player.get_global_position() + Vector3::new(0.0, 0.6, 0.0);
+    let velocity = player
+        .get("velocity".into())
+        .try_to::<Vector3>()
+        .unwrap_or(Vector3::ZERO);
+    let error = deg_to_rad(stats.accuracy_error as f64) as f32;
+
+    let damage = weapon.get("damage".into()).to::<f32>();
+    projectile::fire(scene, shooter, origin, target, velocity, error, damage);
+
+    let sound_shoot = weapon.get("sound_shoot".into()).to::<GString>();
+    play_sound(shooter, &sound_shoot.to_string());
+}
+
+// Damages whatever the attack raycast touches, returning whether it fired
+pub fn fire_hitscan(shooter: &Gd<Node3D>, stats: &EnemyStats, raycast: &mut Gd<RayCast3D>) -> bool {
+    let Some(weapon) = stats.weapon.as_ref() else {
+        return false;
+    };
+
+    raycast.force_raycast_update();
+    if !raycast.is_colliding() {
+        return false;
+    }
+    let Some(mut collider) = raycast.get_collider() else {
+        return false;
+    };
+    if !collider.has_method("damage".into()) {
+        return false;
+    }
+
+    let sound_shoot = weapon.get("sound_shoot".into()).to::<GString>();
+    play_sound(shooter, &sound_shoot.to_string());
+    collider.call("damage".into(), &[weapon.get("damage".into())]);
+    true
+}
+
+// Deferred, as the killer is usually still busy calling into `damage`
+pub fn emit_destroyed(
+    enemy: &mut Gd<Node3D>,
+    killer: Option<Gd<Node3D>>,
+    weapon: Option<Gd<Weapon>>,
+) {
+    let killer = killer.map_or(Variant::nil(), Variant::from);
+    let weapon = weapon.map_or(Variant::nil(), Variant::from);
+    let this = Variant::from(enemy.clone());
+    enemy.call_deferred(
+        "emit_signal".into(),
+        &[
+            Variant::from(StringName::from("enemy_destroyed")),
+            this,
+            killer,
+            weapon,
+        ],
+    );
+}
+
+// Bursts at `position`, rattling a player close by, and scatters the loot
+// around `ground` before freeing the enemy
+pub fn explode(
+    enemy: &mut Gd<Node3D>,
+    stats: &EnemyStats,
+    player: Option<&Gd<Node3D>>,
+    position: Vector3,
+    ground: Vector3,
+) {
+    let impact = load::<PackedScene>("res://objects/impact.tscn");
+    let mut impact = impact.instantiate().unwrap().cast::<Impact>();
+    impact.play_ex().name("shot".into()).done();
+    enemy
+        .get_tree()
+        .unwrap()
+        .get_root()
+        .unwrap()
+        .add_child(impact.clone().upcast());
+    impact.set_global_position(position);
+    impact.set_scale(Vector3::ONE * 4.0);
+
+    play_sound(enemy, &stats.sound_destroy.to_string());
+
+    if let Some(mut player) = player.cloned() {
+        let distance = player.get_global_position().distance_to(position);
+        if distance < 8.0 {
+            player.call_deferred(
+                "add_trauma".into(),
+                &[Variant::from((1.0 - distance / 8.0) * 0.6)],
+            );
+        }
+    }
+
+    if let Some(mut scene) = enemy.get_tree().unwrap().get_current_scene() {
+        loot_drop::drop_loot(&stats.loot, &mut scene, ground);
+    }
+
+    enemy.queue_free();
+}
+
+fn play_sound(node: &Gd<Node3D>, audio_path: &str) {
+    let mut audio = node.get_node_as::<Node>("/root/Audio");
+    audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+}
@@ -0,0 +1,322 @@
+use std::f32::consts::TAU;
+
+use godot::engine::{INode3D, Node3D, Timer};
+use godot::prelude::*;
+use rand::Rng;
+
+use crate::enemy_wave::EnemyWave;
+use crate::weapon::Weapon;
+
+const PATTERN_RANDOM: i32 = 1;
+const PATTERN_SURROUND: i32 = 2;
+
+// Spawns `waves` in order from its Node3D children, waiting `intermission`
+// seconds between them. In survival mode the waves loop, growing by
+// `escalation` each time around, until the player dies.
+#[derive(GodotClass)]
+#[class(base=Node3D)]
+pub struct EnemySpawner {
+    #[export]
+    player: Option<Gd<Node3D>>,
+    #[export]
+    waves: Array<Gd<EnemyWave>>,
+
+    #[export(range = (0.0, 60.0))]
+    intermission: f64,
+    #[export(range = (0.0, 2.0))]
+    escalation: f32,
+    #[export(range = (1.0, 30.0))]
+    surround_radius: f32,
+
+    #[export]
+    survival: bool,
+    #[export]
+    autostart: bool,
+    // Hand-placed enemies of the level this spawner takes over, freed on
+    // ready so only spawned ones count towards a wave
+    #[export]
+    placed_enemies: Option<Gd<Node>>,
+
+    spawn_points: Vec<Gd<Node3D>>,
+    spawn_timer: OnReady<Gd<Timer>>,
+    intermission_timer: OnReady<Gd<Timer>>,
+
+    running: bool,
+    wave_index: i32,
+    wave: Option<Gd<EnemyWave>>,
+    remaining: i32,
+    spawned: i32,
+    total: i32,
+    alive: i32,
+    waves_survived: i32,
+
+    base: Base<Node3D>,
+}
+
+#[godot_api]
+impl INode3D for EnemySpawner {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            player: None,
+            waves: Array::new(),
+
+            intermission: 5.0,
+            escalation: 0.5,
+            surround_radius: 10.0,
+
+            survival: false,
+            autostart: true,
+            placed_enemies: None,
+
+            spawn_points: Vec::new(),
+            spawn_timer: OnReady::manual(),
+            intermission_timer: OnReady::manual(),
+
+            running: false,
+            wave_index: 0,
+            wave: None,
+            remaining: 0,
+            spawned: 0,
+            total: 0,
+            alive: 0,
+            waves_survived: 0,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        if let Some(mut placed_enemies) = self.placed_enemies.take() {
+            placed_enemies.queue_free();
+        }
+
+        self.spawn_points = self
+            .base()
+            .get_children()
+            .iter_shared()
+            .filter_map(|x| x.try_cast::<Node3D>().ok())
+            .collect();
+
+        let mut spawn_timer = Timer::new_alloc();
+        spawn_timer.connect("timeout".into(), self.base().callable("_on_spawn_timeout"));
+        self.base_mut().add_child(spawn_timer.clone().upcast());
+        self.spawn_timer.init(spawn_timer);
+
+        let mut intermission_timer = Timer::new_alloc();
+        intermission_timer.set_one_shot(true);
+        intermission_timer.connect(
+            "timeout".into(),
+            self.base().callable("_on_intermission_timeout"),
+        );
+        self.base_mut()
+            .add_child(intermission_timer.clone().upcast());
+        self.intermission_timer.init(intermission_timer);
+
+        if let Some(mut player) = self.player.clone() {
+            player.connect(
+                "player_died".into(),
+                self.base().callable("_on_player_died"),
+            );
+        }
+
+        if self.autostart {
+            self.start();
+        }
+    }
+}
+
+#[godot_api]
+impl EnemySpawner {
+    #[func]
+    fn start(&mut self) {
+        self.running = true;
+        self.wave_index = 0;
+        self.waves_survived = 0;
+        self.begin_intermission();
+    }
+
+    #[func]
+    fn stop(&mut self) {
+        self.running = false;
+        self.spawn_timer.stop();
+        self.intermission_timer.stop();
+    }
+
+    #[func]
+    fn get_waves_survived(&self) -> i32 {
+        self.waves_survived
+    }
+
+    #[func]
+    fn _on_intermission_timeout(&mut self) {
+        if !self.running {
+            return;
+        }
+
+        let count = self.waves.len() as i32;
+        if count == 0 || (!self.survival && self.wave_index >= count) {
+            self.running = false;
+            self.base_mut().emit_signal("waves_completed".into(), &[]);
+            return;
+        }
+
+        let wave = self.waves.get((self.wave_index % count) as usize);
+        let difficulty = 1.0 + self.escalation * (self.wave_index / count) as f32;
+
+        self.total = (wave.get("count".into()).to::<i32>() as f32 * difficulty).round() as i32;
+        self.remaining = self.total;
+        self.spawned = 0;
+
+        let delay = wave.get("delay".into()).to::<f64>() / difficulty as f64;
+        self.spawn_timer.set_wait_time(delay.max(0.05));
+        self.wave = Some(wave);
+
+        let wave_number = self.wave_index + 1;
+        self.base_mut()
+            .emit_signal("wave_started".into(), &[Variant::from(wave_number)]);
+
+        self.spawn_enemy();
+        if self.remaining > 0 {
+            self.spawn_timer.start();
+        }
+    }
+
+    #[func]
+    fn _on_spawn_timeout(&mut self) {
+        if !self.running || self.remaining <= 0 {
+            self.spawn_timer.stop();
+            return;
+        }
+
+        self.spawn_enemy();
+    }
+
+    #[func]
+    fn _on_enemy_exiting(&mut self) {
+        self.alive -= 1;
+        if !self.running || self.alive > 0 || self.remaining > 0 {
+            return;
+        }
+
+        // The whole scene is being torn down, not just this enemy
+        if self.base().is_queued_for_deletion() || !self.base().is_inside_tree() {
+            return;
+        }
+
+        self.waves_survived += 1;
+        let wave_number = self.wave_index + 1;
+        self.base_mut()
+            .emit_signal("wave_cleared".into(), &[Variant::from(wave_number)]);
+
+        self.wave_index += 1;
+        self.begin_intermission();
+    }
+
+    // Passed on so scoring only has to listen to the spawner
+    #[func]
+    fn _on_enemy_destroyed(&mut self, enemy: Variant, killer: Variant, weapon: Variant) {
+        self.base_mut()
+            .emit_signal("enemy_destroyed".into(), &[enemy, killer, weapon]);
+    }
+
+    #[func]
+    fn _on_player_died(&mut self) {
+        if !self.survival || !self.running {
+            return;
+        }
+
+        self.stop();
+        let waves_survived = self.waves_survived;
+        self.base_mut()
+            .emit_signal("survival_ended".into(), &[Variant::from(waves_survived)]);
+    }
+
+    fn begin_intermission(&mut self) {
+        self.intermission_timer
+            .start_ex()
+            .time_sec(self.intermission.max(0.05))
+            .done();
+    }
+
+    fn spawn_enemy(&mut self) {
+        let Some(wave) = self.wave.clone() else {
+            return;
+        };
+        let Ok(scene) = wave.get("scene".into()).try_to::<Gd<PackedScene>>() else {
+            godot_error!("Enemy wave has no scene to spawn");
+            self.remaining = 0;
+            return;
+        };
+        let Some(mut enemy) = scene
+            .instantiate()
+            .and_then(|x| x.try_cast::<Node3D>().ok())
+        else {
+            return;
+        };
+
+        let pattern = wave.get("pattern".into()).to::<i32>();
+        let position = self.spawn_position(pattern);
+        enemy.set_position(self.base().to_local(position));
+
+        if let Some(player) = self.player.clone() {
+            enemy.set("player".into(), Variant::from(player));
+        }
+        let archetype = wave.get("archetype".into());
+        if !archetype.is_nil() {
+            enemy.set("archetype".into(), archetype);
+        }
+
+        enemy.connect(
+            "tree_exiting".into(),
+            self.base().callable("_on_enemy_exiting"),
+        );
+        if enemy.has_signal("enemy_destroyed".into()) {
+            enemy.connect(
+                "enemy_destroyed".into(),
+                self.base().callable("_on_enemy_destroyed"),
+            );
+        }
+        self.base_mut().add_child(enemy.upcast());
+
+        self.alive += 1;
+        self.spawned += 1;
+        self.remaining -= 1;
+    }
+
+    fn spawn_position(&self, pattern: i32) -> Vector3 {
+        let origin = self.base().get_global_position();
+        if pattern == PATTERN_SURROUND {
+            if let Some(player) = self.player.as_ref() {
+                let angle = self.spawned as f32 / self.total.max(1) as f32 * TAU;
+                return player.get_global_position()
+                    + Vector3::new(angle.cos(), 0.0, angle.sin()) * self.surround_radius
+                    + Vector3::new(0.0, 2.5, 0.0);
+            }
+        }
+
+        if self.spawn_points.is_empty() {
+            return origin;
+        }
+
+        let index = match pattern {
+            PATTERN_RANDOM => rand::thread_rng().gen_range(0..self.spawn_points.len()),
+            _ => self.spawned as usize % self.spawn_points.len(),
+        };
+        self.spawn_points[index].get_global_position()
+    }
+
+    #[signal]
+    fn wave_started(wave: i32);
+
+    #[signal]
+    fn wave_cleared(wave: i32);
+
+    #[signal]
+    fn waves_completed();
+
+    #[signal]
+    fn enemy_destroyed(enemy: Gd<Node3D>, killer: Gd<Node3D>, weapon: Gd<Weapon>);
+
+    #[signal]
+    fn survival_ended(waves_survived: i32);
+}
@@ -0,0 +1,305 @@
+use std::f32::consts::TAU;
+
+use godot::engine::base_material_3d::{Flags, ShadingMode};
+use godot::engine::mesh::PrimitiveType;
+use godot::engine::{INode3D, ImmediateMesh, MeshInstance3D, Node3D, StandardMaterial3D};
+use godot::prelude::*;
+
+// Coordinates every flying enemy in the scene: keeps them from stacking on
+// each other, spreads the ones fighting the player evenly around them and
+// hands out a limited number of attack tokens so only a few fire at once
+#[derive(GodotClass)]
+#[class(base=Node3D)]
+pub struct EnemySquad {
+    #[export]
+    player: Option<Gd<Node3D>>,
+
+    #[export(range = (1.0, 10.0))]
+    attack_tokens: i32,
+    #[export(range = (0.0, 10.0))]
+    token_time: f64,
+
+    #[export(range = (0.0, 10.0))]
+    separation_radius: f32,
+    #[export(range = (0.0, 10.0))]
+    separation_strength: f32,
+    #[export(range = (0.0, 30.0))]
+    cohesion_radius: f32,
+    #[export(range = (0.0, 2.0))]
+    cohesion_strength: f32,
+
+    #[export]
+    debug: bool,
+
+    holders: Vec<(InstanceId, f64)>,
+    resting: Vec<(InstanceId, f64)>,
+    gizmo: Option<Gd<ImmediateMesh>>,
+
+    base: Base<Node3D>,
+}
+
+struct Member {
+    enemy: Gd<Node3D>,
+    position: Vector3,
+    fighting: bool,
+    attacking: bool,
+    roaming: bool,
+}
+
+#[godot_api]
+impl INode3D for EnemySquad {
+    fn init(base: Base<Node3D>) -> Self {
+        Self {
+            player: None,
+
+            attack_tokens: 2,
+            token_time: 3.0,
+
+            separation_radius: 2.5,
+            separation_strength: 2.0,
+            cohesion_radius: 8.0,
+            cohesion_strength: 0.3,
+
+            debug: false,
+
+            holders: Vec::new(),
+            resting: Vec::new(),
+            gizmo: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        if self.debug {
+            let mut material = StandardMaterial3D::new_gd();
+            material.set_shading_mode(ShadingMode::UNSHADED);
+            material.set_flag(Flags::ALBEDO_FROM_VERTEX_COLOR, true);
+
+            let gizmo = ImmediateMesh::new_gd();
+            let mut instance = MeshInstance3D::new_alloc();
+            instance.set_as_top_level(true);
+            instance.set_mesh(gizmo.clone().upcast());
+            instance.set_material_override(material.upcast());
+            self.base_mut().add_child(instance.upcast());
+            self.gizmo = Some(gizmo);
+        }
+    }
+
+    fn process(&mut self, delta: f64) {
+        let Some(player) = self.player.clone() else {
+            return;
+        };
+        let player_position = player.get_global_position();
+
+        let mut members = self.members();
+        let flanks = self.flanks(&members, player_position);
+        let tokens = self.allocate_tokens(&members, player_position, delta);
+
+        for i in 0..members.len() {
+            let steer = self.steer(&members, i);
+            let can_fire = tokens.contains(&members[i].enemy.instance_id());
+
+            members[i].enemy.call(
+                "set_squad_orders".into(),
+                &[
+                    Variant::from(flanks[i]),
+                    Variant::from(steer),
+                    Variant::from(can_fire),
+                ],
+            );
+        }
+
+        self.draw(&members, &flanks, &tokens, player_position);
+    }
+}
+
+#[godot_api]
+impl EnemySquad {
+    fn members(&self) -> Vec<Member> {
+        self.base()
+            .get_tree()
+            .unwrap()
+            .get_nodes_in_group("enemy".into())
+            .iter_shared()
+            .filter(|x| x.is_class("Enemy".into()))
+            .filter_map(|x| x.try_cast::<Node3D>().ok())
+            .map(|mut enemy| {
+                let state = enemy.call("get_state".into(), &[]).to::<GString>();
+                let state = state.to_string();
+                Member {
+                    position: enemy.get_global_position(),
+                    fighting: state == "chase" || state == "attack",
+                    attacking: state == "attack",
+                    roaming: state == "patrol" || state == "investigate",
+                    enemy,
+                }
+            })
+            .collect()
+    }
+
+    // Pushes away from close neighbours and, while patrolling or searching,
+    // drifts towards the middle of the group so they move as a pack
+    fn steer(&self, members: &[Member], index: usize) -> Vector3 {
+        let position = members[index].position;
+        let mut separation = Vector3::ZERO;
+        let mut center = Vector3::ZERO;
+        let mut neighbours = 0;
+
+        for (i, other) in members.iter().enumerate() {
+            if i == index {
+                continue;
+            }
+
+            let offset = position - other.position;
+            let distance = offset.length();
+            if distance < self.separation_radius && distance > 0.001 {
+                separation += offset / distance * (1.0 - distance / self.separation_radius);
+            }
+            if distance < self.cohesion_radius {
+                center += other.position;
+                neighbours += 1;
+            }
+        }
+
+        let mut steer = separation * self.separation_strength;
+        if members[index].roaming && neighbours > 0 {
+            steer += (center / neighbours as f32 - position) * self.cohesion_strength;
+        }
+        steer
+    }
+
+    // Spreads the fighting enemies evenly around the player, keeping the
+    // order they already circle in so nobody has to cross over
+    fn flanks(&self, members: &[Member], player_position: Vector3) -> Vec<Vector3> {
+        let mut flanks = vec![Vector3::ZERO; members.len()];
+
+        let mut fighting: Vec<(usize, f32)> = members
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.fighting)
+            .map(|(i, x)| {
+                let offset = x.position - player_position;
+                (i, offset.z.atan2(offset.x))
+            })
+            .collect();
+        if fighting.is_empty() {
+            return flanks;
+        }
+        fighting.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        // Rotates the evenly spaced slots to best fit where everyone already is
+        let step = TAU / fighting.len() as f32;
+        let (mut x, mut y) = (0.0, 0.0);
+        for (k, (_, angle)) in fighting.iter().enumerate() {
+            let offset = angle - k as f32 * step;
+            x += offset.cos();
+            y += offset.sin();
+        }
+        let base = y.atan2(x);
+
+        for (k, (i, _)) in fighting.iter().enumerate() {
+            let angle = base + k as f32 * step;
+            flanks[*i] = Vector3::new(angle.cos(), 0.0, angle.sin());
+        }
+        flanks
+    }
+
+    // Holders keep their token for `token_time` and then rest for as long,
+    // so everyone in range gets a turn
+    fn allocate_tokens(
+        &mut self,
+        members: &[Member],
+        player_position: Vector3,
+        delta: f64,
+    ) -> Vec<InstanceId> {
+        let attacking: Vec<&Member> = members.iter().filter(|x| x.attacking).collect();
+        let is_attacking = |id: InstanceId| attacking.iter().any(|x| x.enemy.instance_id() == id);
+
+        for (_, time) in self.resting.iter_mut() {
+            *time -= delta;
+        }
+        self.resting.retain(|(_, time)| *time > 0.0);
+
+        for (_, time) in self.holders.iter_mut() {
+            *time += delta;
+        }
+        let token_time = self.token_time;
+        let (expired, holders): (Vec<_>, Vec<_>) = self
+            .holders
+            .drain(..)
+            .filter(|(id, _)| is_attacking(*id))
+            .partition(|(_, time)| token_time > 0.0 && *time >= token_time);
+        self.holders = holders;
+        self.resting
+            .extend(expired.into_iter().map(|(id, _)| (id, token_time)));
+
+        let mut candidates: Vec<(bool, f32, InstanceId)> = attacking
+            .iter()
+            .map(|x| (x.enemy.instance_id(), x.position))
+            .filter(|(id, _)| !self.holders.iter().any(|(x, _)| x == id))
+            .map(|(id, position)| {
+                let resting = self.resting.iter().any(|(x, _)| *x == id);
+                (resting, position.distance_to(player_position), id)
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        let free = (self.attack_tokens.max(1) as usize).saturating_sub(self.holders.len());
+        for (_, _, id) in candidates.into_iter().take(free) {
+            self.resting.retain(|(x, _)| *x != id);
+            self.holders.push((id, 0.0));
+        }
+
+        self.holders.iter().map(|(id, _)| *id).collect()
+    }
+
+    fn draw(
+        &mut self,
+        members: &[Member],
+        flanks: &[Vector3],
+        tokens: &[InstanceId],
+        player_position: Vector3,
+    ) {
+        let Some(mut gizmo) = self.gizmo.clone() else {
+            return;
+        };
+
+        gizmo.clear_surfaces();
+        if !members.iter().any(|x| x.fighting) {
+            return;
+        }
+        gizmo.surface_begin(PrimitiveType::LINES);
+
+        for (member, flank) in members.iter().zip(flanks) {
+            let holding = tokens.contains(&member.enemy.instance_id());
+            let color = if holding {
+                Color::from_rgb(1.0, 0.3, 0.2)
+            } else {
+                Color::from_rgb(0.3, 0.8, 1.0)
+            };
+            gizmo.surface_set_color(color);
+
+            // Spoke out to the assigned slot, then on to the enemy itself
+            if *flank != Vector3::ZERO {
+                let mut offset = member.position - player_position;
+                offset.y = 0.0;
+                let slot = player_position
+                    + *flank * offset.length()
+                    + Vector3::new(0.0, member.position.y - player_position.y, 0.0);
+
+                gizmo.surface_add_vertex(player_position);
+                gizmo.surface_add_vertex(slot);
+                gizmo.surface_add_vertex(slot);
+                gizmo.surface_add_vertex(member.position);
+            }
+
+            if holding {
+                gizmo.surface_add_vertex(member.position + Vector3::new(0.0, 0.75, 0.0));
+                gizmo.surface_add_vertex(member.position + Vector3::new(0.0, 1.5, 0.0));
+            }
+        }
+
+        gizmo.surface_end();
+    }
+}
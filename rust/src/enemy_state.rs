@@ -0,0 +1,319 @@
+// Behavior states for `Enemy`, kept free of Godot types so the transition
+// rules can be reasoned about (and exercised) without a running engine
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyState {
+    Idle,
+    Patrol,
+    Alert,
+    Chase,
+    Attack,
+    Investigate,
+    Flee,
+}
+
+impl EnemyState {
+    pub fn name(self) -> &'static str {
+        match self {
+            EnemyState::Idle => "idle",
+            EnemyState::Patrol => "patrol",
+            EnemyState::Alert => "alert",
+            EnemyState::Chase => "chase",
+            EnemyState::Attack => "attack",
+            EnemyState::Investigate => "investigate",
+            EnemyState::Flee => "flee",
+        }
+    }
+
+    pub fn is_engaged(self) -> bool {
+        matches!(
+            self,
+            EnemyState::Alert | EnemyState::Chase | EnemyState::Attack | EnemyState::Investigate
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    pub detection_radius: f32,
+    pub attack_radius: f32,
+    pub lose_sight_timeout: f64,
+    pub alert_time: f64,
+    pub flee_health: f32,
+    pub idle_time: f64,
+}
+
+// What the enemy knows about the player this frame
+#[derive(Clone, Copy, Debug)]
+pub struct Senses {
+    pub can_see: bool,
+    pub heard: bool,
+    pub reached_lead: bool,
+    pub distance: f32,
+    pub time_since_seen: f64,
+    pub time_in_state: f64,
+    pub health: f32,
+    pub can_patrol: bool,
+}
+
+pub fn next_state(state: EnemyState, senses: &Senses, thresholds: &Thresholds) -> EnemyState {
+    let detected = senses.can_see && senses.distance <= thresholds.detection_radius;
+    let lost = !senses.can_see && senses.time_since_seen >= thresholds.lose_sight_timeout;
+    let resting = if senses.can_patrol {
+        EnemyState::Patrol
+    } else {
+        EnemyState::Idle
+    };
+
+    if state.is_engaged() && senses.health <= thresholds.flee_health {
+        return EnemyState::Flee;
+    }
+
+    match state {
+        EnemyState::Idle => {
+            if detected {
+                EnemyState::Alert
+            } else if senses.heard {
+                EnemyState::Investigate
+            } else if senses.can_patrol && senses.time_in_state >= thresholds.idle_time {
+                EnemyState::Patrol
+            } else {
+                state
+            }
+        }
+        EnemyState::Patrol => {
+            if detected {
+                EnemyState::Alert
+            } else if senses.heard {
+                EnemyState::Investigate
+            } else {
+                state
+            }
+        }
+        EnemyState::Alert => {
+            if !senses.can_see {
+                EnemyState::Investigate
+            } else if senses.time_in_state >= thresholds.alert_time {
+                EnemyState::Chase
+            } else {
+                state
+            }
+        }
+        EnemyState::Chase => {
+            if !senses.can_see {
+                EnemyState::Investigate
+            } else if senses.distance <= thresholds.attack_radius {
+                EnemyState::Attack
+            } else {
+                state
+            }
+        }
+        EnemyState::Attack => {
+            // Some slack so the enemy doesn't flicker at the edge of its range
+            if !senses.can_see || senses.distance > thresholds.attack_radius * 1.2 {
+                EnemyState::Chase
+            } else {
+                state
+            }
+        }
+        EnemyState::Investigate => {
+            // Give up once the last-known position turned up nothing, or
+            // when it can't be reached in a reasonable time
+            if detected {
+                EnemyState::Chase
+            } else if lost
+                && (senses.reached_lead
+                    || senses.time_in_state >= thresholds.lose_sight_timeout * 3.0)
+            {
+                resting
+            } else {
+                state
+            }
+        }
+        EnemyState::Flee => {
+            if lost || senses.distance > thresholds.detection_radius * 1.5 {
+                resting
+            } else {
+                state
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        detection_radius: 12.0,
+        attack_radius: 8.0,
+        lose_sight_timeout: 3.0,
+        alert_time: 0.5,
+        flee_health: 0.2,
+        idle_time: 2.0,
+    };
+
+    // Player out of sight and far away, enemy at full health
+    fn calm() -> Senses {
+        Senses {
+            can_see: false,
+            heard: false,
+            reached_lead: false,
+            distance: 50.0,
+            time_since_seen: 0.0,
+            time_in_state: 0.0,
+            health: 1.0,
+            can_patrol: true,
+        }
+    }
+
+    fn spotted(distance: f32) -> Senses {
+        Senses {
+            can_see: true,
+            distance,
+            ..calm()
+        }
+    }
+
+    fn next(state: EnemyState, senses: Senses) -> EnemyState {
+        next_state(state, &senses, &THRESHOLDS)
+    }
+
+    #[test]
+    fn idle_starts_patrolling_after_idle_time() {
+        let waiting = Senses {
+            time_in_state: 1.0,
+            ..calm()
+        };
+        let rested = Senses {
+            time_in_state: 2.0,
+            ..calm()
+        };
+        assert_eq!(next(EnemyState::Idle, waiting), EnemyState::Idle);
+        assert_eq!(next(EnemyState::Idle, rested), EnemyState::Patrol);
+    }
+
+    #[test]
+    fn idle_stays_put_without_patrol_points() {
+        let senses = Senses {
+            time_in_state: 10.0,
+            can_patrol: false,
+            ..calm()
+        };
+        assert_eq!(next(EnemyState::Idle, senses), EnemyState::Idle);
+    }
+
+    #[test]
+    fn detection_raises_alert() {
+        assert_eq!(next(EnemyState::Idle, spotted(10.0)), EnemyState::Alert);
+        assert_eq!(next(EnemyState::Patrol, spotted(10.0)), EnemyState::Alert);
+        assert_eq!(next(EnemyState::Patrol, spotted(20.0)), EnemyState::Patrol);
+    }
+
+    #[test]
+    fn noise_starts_an_investigation() {
+        let senses = Senses {
+            heard: true,
+            ..calm()
+        };
+        assert_eq!(next(EnemyState::Idle, senses), EnemyState::Investigate);
+        assert_eq!(next(EnemyState::Patrol, senses), EnemyState::Investigate);
+    }
+
+    #[test]
+    fn alert_turns_into_chase_after_alert_time() {
+        let startled = Senses {
+            time_in_state: 0.1,
+            ..spotted(10.0)
+        };
+        let ready = Senses {
+            time_in_state: 0.5,
+            ..spotted(10.0)
+        };
+        assert_eq!(next(EnemyState::Alert, startled), EnemyState::Alert);
+        assert_eq!(next(EnemyState::Alert, ready), EnemyState::Chase);
+    }
+
+    #[test]
+    fn chase_attacks_within_attack_radius() {
+        assert_eq!(next(EnemyState::Chase, spotted(10.0)), EnemyState::Chase);
+        assert_eq!(next(EnemyState::Chase, spotted(8.0)), EnemyState::Attack);
+    }
+
+    #[test]
+    fn attack_keeps_some_slack_before_chasing_again() {
+        assert_eq!(next(EnemyState::Attack, spotted(9.0)), EnemyState::Attack);
+        assert_eq!(next(EnemyState::Attack, spotted(10.0)), EnemyState::Chase);
+        assert_eq!(next(EnemyState::Attack, calm()), EnemyState::Chase);
+    }
+
+    #[test]
+    fn losing_sight_leads_to_investigation() {
+        assert_eq!(next(EnemyState::Alert, calm()), EnemyState::Investigate);
+        assert_eq!(next(EnemyState::Chase, calm()), EnemyState::Investigate);
+    }
+
+    #[test]
+    fn investigation_gives_up_after_lose_sight_timeout() {
+        let searching = Senses {
+            time_since_seen: 1.0,
+            reached_lead: true,
+            ..calm()
+        };
+        let lost = Senses {
+            time_since_seen: 3.0,
+            reached_lead: true,
+            ..calm()
+        };
+        let stuck = Senses {
+            time_since_seen: 3.0,
+            time_in_state: 9.0,
+            ..calm()
+        };
+        assert_eq!(
+            next(EnemyState::Investigate, searching),
+            EnemyState::Investigate
+        );
+        assert_eq!(next(EnemyState::Investigate, lost), EnemyState::Patrol);
+        assert_eq!(next(EnemyState::Investigate, stuck), EnemyState::Patrol);
+
+        let idle = Senses {
+            can_patrol: false,
+            ..lost
+        };
+        assert_eq!(next(EnemyState::Investigate, idle), EnemyState::Idle);
+    }
+
+    #[test]
+    fn investigation_chases_when_the_player_shows_up() {
+        assert_eq!(
+            next(EnemyState::Investigate, spotted(10.0)),
+            EnemyState::Chase
+        );
+    }
+
+    #[test]
+    fn low_health_flees_once_engaged() {
+        let hurt = Senses {
+            health: 0.2,
+            ..spotted(5.0)
+        };
+        assert_eq!(next(EnemyState::Chase, hurt), EnemyState::Flee);
+        assert_eq!(next(EnemyState::Attack, hurt), EnemyState::Flee);
+        assert_eq!(next(EnemyState::Idle, hurt), EnemyState::Alert);
+    }
+
+    #[test]
+    fn flee_ends_once_out_of_range() {
+        let hurt = Senses {
+            health: 0.1,
+            ..spotted(10.0)
+        };
+        let escaped = Senses {
+            health: 0.1,
+            ..spotted(20.0)
+        };
+        assert_eq!(next(EnemyState::Flee, hurt), EnemyState::Flee);
+        assert_eq!(next(EnemyState::Flee, escaped), EnemyState::Patrol);
+    }
+}
@@ -0,0 +1,38 @@
+use godot::engine::Resource;
+use godot::prelude::*;
+
+use crate::enemy_archetype::EnemyArchetype;
+
+#[derive(GodotClass)]
+#[class(base=Resource)]
+pub struct EnemyWave {
+    #[export]
+    scene: Option<Gd<PackedScene>>,
+    #[export]
+    archetype: Option<Gd<EnemyArchetype>>,
+
+    #[export(range = (1.0, 50.0))]
+    count: i32,
+    #[export(range = (0.0, 10.0))]
+    delay: f64,
+    #[export(enum = (Sequential, Random, Surround))]
+    pattern: i32,
+
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl IResource for EnemyWave {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            scene: None,
+            archetype: None,
+
+            count: 4,
+            delay: 1.0,
+            pattern: 0,
+
+            base,
+        }
+    }
+}
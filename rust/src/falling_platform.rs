@@ -0,0 +1,115 @@
+use godot::engine::{AnimatableBody3D, Area3D, IAnimatableBody3D, Node3D};
+use godot::prelude::*;
+use rand::Rng;
+
+use crate::player::Player;
+
+enum FallingState {
+    Idle,
+    Shaking,
+    Falling,
+}
+
+#[derive(GodotClass)]
+#[class(base=AnimatableBody3D)]
+pub struct FallingPlatform {
+    #[export(range = (0.0, 5.0))]
+    shake_time: f64,
+    #[export(range = (0.0, 0.5))]
+    shake_strength: f32,
+    #[export(range = (0.0, 30.0))]
+    reset_delay: f64,
+
+    trigger: OnReady<Gd<Area3D>>,
+
+    state: FallingState,
+    origin: Vector3,
+    time: f64,
+    fall_velocity: f32,
+
+    base: Base<AnimatableBody3D>,
+}
+
+#[godot_api]
+impl IAnimatableBody3D for FallingPlatform {
+    fn init(base: Base<AnimatableBody3D>) -> Self {
+        Self {
+            shake_time: 0.75,
+            shake_strength: 0.05,
+            reset_delay: 4.0,
+
+            trigger: OnReady::manual(),
+
+            state: FallingState::Idle,
+            origin: Vector3::ZERO,
+            time: 0.0,
+            fall_velocity: 0.0,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.origin = self.base().get_position();
+
+        self.trigger.init(self.base().get_node_as("Trigger"));
+        self.trigger.connect(
+            "body_entered".into(),
+            self.base().callable("_on_body_entered"),
+        );
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        match self.state {
+            FallingState::Idle => {}
+            FallingState::Shaking => {
+                self.time += delta;
+
+                let mut position = self.origin;
+                position.x += rand::thread_rng().gen_range(-1.0..1.0) * self.shake_strength;
+                position.z += rand::thread_rng().gen_range(-1.0..1.0) * self.shake_strength;
+                self.base_mut().set_position(position);
+
+                if self.time >= self.shake_time {
+                    self.time = 0.0;
+                    self.fall_velocity = 0.0;
+                    self.state = FallingState::Falling;
+                }
+            }
+            FallingState::Falling => {
+                self.time += delta;
+                self.fall_velocity += 20.0 * delta as f32;
+
+                let mut position = self.base().get_position();
+                position.y -= self.fall_velocity * delta as f32;
+                self.base_mut().set_position(position);
+
+                if self.time >= self.reset_delay {
+                    self.reset();
+                }
+            }
+        }
+    }
+}
+
+#[godot_api]
+impl FallingPlatform {
+    #[func]
+    fn _on_body_entered(&mut self, body: Gd<Node3D>) {
+        if !matches!(self.state, FallingState::Idle) || body.try_cast::<Player>().is_err() {
+            return;
+        }
+
+        self.time = 0.0;
+        self.state = FallingState::Shaking;
+    }
+
+    fn reset(&mut self) {
+        self.time = 0.0;
+        self.fall_velocity = 0.0;
+        self.state = FallingState::Idle;
+
+        let origin = self.origin;
+        self.base_mut().set_position(origin);
+    }
+}
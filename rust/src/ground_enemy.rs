@@ -0,0 +1,454 @@
+use godot::engine::object::ConnectFlags;
+use godot::engine::{
+    AnimatedSprite3D, CharacterBody3D, ICharacterBody3D, NavigationAgent3D, Node3D, RayCast3D,
+    Timer,
+};
+use godot::prelude::*;
+use rand::Rng;
+
+use crate::enemy_archetype::EnemyArchetype;
+use crate::enemy_common::{self, Awareness, EnemyStats, HitReaction};
+use crate::enemy_state::{EnemyState, Senses};
+use crate::weapon::Weapon;
+
+#[derive(GodotClass)]
+#[class(base=CharacterBody3D)]
+struct GroundEnemy {
+    #[export]
+    player: Option<Gd<Node3D>>,
+
+    #[export]
+    archetype: Option<Gd<EnemyArchetype>>,
+    #[export]
+    patrol_points: PackedVector3Array,
+
+    stats: OnReady<EnemyStats>,
+
+    agent: OnReady<Gd<NavigationAgent3D>>,
+    raycast: OnReady<Gd<RayCast3D>>,
+    sight: OnReady<Gd<RayCast3D>>,
+    muzzle: OnReady<Gd<AnimatedSprite3D>>,
+    reaction: OnReady<HitReaction>,
+
+    health: f32,
+    gravity: f32,
+    jumping: bool,
+    navigation_ready: bool,
+    repath: bool,
+    destroyed: bool,
+    death_time: f64,
+    spin_axis: Vector3,
+
+    awareness: Awareness,
+    origin: Vector3,
+    patrol_index: usize,
+
+    base: Base<CharacterBody3D>,
+}
+
+#[godot_api]
+impl ICharacterBody3D for GroundEnemy {
+    fn init(base: Base<CharacterBody3D>) -> Self {
+        Self {
+            player: None,
+
+            archetype: None,
+            patrol_points: PackedVector3Array::new(),
+
+            stats: OnReady::manual(),
+
+            agent: OnReady::manual(),
+            raycast: OnReady::manual(),
+            sight: OnReady::manual(),
+            muzzle: OnReady::manual(),
+            reaction: OnReady::manual(),
+
+            health: 100.0,
+            gravity: 0.0,
+            jumping: false,
+            navigation_ready: true,
+            repath: false,
+            destroyed: false,
+            death_time: 0.0,
+            spin_axis: Vector3::RIGHT,
+
+            awareness: Awareness::default(),
+            origin: Vector3::ZERO,
+            patrol_index: 0,
+
+            base,
+        }
+    }
+
+    fn enter_tree(&mut self) {
+        self.base_mut().add_to_group("enemy".into());
+    }
+
+    fn ready(&mut self) {
+        self.origin = self.base().get_position();
+
+        self.agent.init(self.base().get_node_as("NavigationAgent"));
+        self.raycast.init(self.base().get_node_as("RayCast"));
+        self.muzzle.init(self.base().get_node_as("Muzzle"));
+
+        self.stats.init(EnemyStats::load(self.archetype.clone()));
+        self.health = self.stats.max_health;
+        let mut enemy = self.base().clone().upcast::<Node3D>();
+        let model = enemy_common::equip(&mut enemy, &self.stats, &mut self.raycast);
+        self.reaction.init(HitReaction::new(model));
+
+        let mut sight = RayCast3D::new_alloc();
+        sight.set_position(Vector3::new(0.0, 1.0, 0.0));
+        self.base_mut().add_child(sight.clone().upcast());
+        self.sight.init(sight);
+
+        // Deferred, the agent emits this from inside `get_next_path_position`
+        let callable = self.base().callable("_on_link_reached");
+        self.agent
+            .connect_ex("link_reached".into(), callable)
+            .flags(ConnectFlags::DEFERRED.ord() as u32)
+            .done();
+
+        // Wait for a navigation mesh that is still being baked
+        let navigation = self
+            .base()
+            .get_tree()
+            .unwrap()
+            .get_first_node_in_group("level_navigation".into());
+        if let Some(mut navigation) = navigation {
+            if !navigation.call("is_baked".into(), &[]).to::<bool>() {
+                self.navigation_ready = false;
+                navigation.connect(
+                    "bake_finished".into(),
+                    self.base().callable("_on_navigation_baked"),
+                );
+            }
+        }
+
+        if let Some(mut player) = self.player.clone() {
+            if player.has_signal("noise_made".into()) {
+                player.connect("noise_made".into(), self.base().callable("_on_noise_made"));
+            }
+        }
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if self.destroyed {
+            self.topple(delta);
+            return;
+        }
+
+        if self.reaction.update(delta, &self.stats) {
+            self.base().get_node_as::<Timer>("Timer").start();
+        }
+
+        // Reeling, the knockback bleeds off and nothing else happens
+        let staggered = self.reaction.is_staggered();
+        let goal = if staggered { None } else { self.decide(delta) };
+
+        let position = self.base().get_position();
+        let player_position = self.player.as_ref().map(|x| x.get_position());
+        let mut velocity = self.base().get_velocity();
+        let mut heading = Vector3::ZERO;
+        if let Some(goal) = goal.filter(|_| self.navigation_ready) {
+            if self.repath || self.agent.get_target_position().distance_to(goal) > 0.5 {
+                self.repath = false;
+                self.agent.set_target_position(goal);
+            }
+
+            if !self.agent.is_navigation_finished() {
+                let mut offset = self.agent.get_next_path_position() - position;
+                offset.y = 0.0;
+                heading = offset.normalized() * self.stats.move_speed;
+            }
+        }
+
+        // Mid-jump the enemy is committed to the arc it took off with
+        if !self.jumping {
+            let horizontal = Vector3::new(velocity.x, 0.0, velocity.z)
+                .move_toward(heading, self.stats.acceleration * delta as f32);
+            velocity.x = horizontal.x;
+            velocity.z = horizontal.z;
+        }
+
+        if self.base().is_on_floor() && self.gravity > 0.0 {
+            self.gravity = 0.0;
+            self.jumping = false;
+        } else {
+            self.gravity += 20.0 * delta as f32;
+        }
+        velocity.y = -self.gravity;
+
+        self.base_mut().set_velocity(velocity);
+        self.base_mut().move_and_slide();
+
+        if heading != Vector3::ZERO {
+            self.face(position + heading);
+        } else if let Some(player_position) =
+            player_position.filter(|_| !staggered && self.awareness.state.is_engaged())
+        {
+            self.face(player_position);
+        }
+    }
+}
+
+#[godot_api]
+impl GroundEnemy {
+    #[func]
+    fn damage(&mut self, amount: f32) {
+        self.hurt(amount, None, None);
+    }
+
+    // Like `damage`, but credits whoever fired, and with what, should the
+    // hit be fatal
+    #[func]
+    fn damage_by(&mut self, amount: f32, source: Gd<Node3D>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
+        // Shoved away from the shooter along the ground
+        if let Some(weapon) = weapon.as_ref() {
+            let knockback = weapon.get("knockback".into()).to::<f32>() * self.stats.knockback_scale;
+            let mut direction = self.base().get_global_position() - source.get_global_position();
+            direction.y = 0.0;
+            if direction.length() > 0.01 {
+                let velocity = self.base().get_velocity() + direction.normalized() * knockback;
+                self.base_mut().set_velocity(velocity);
+            }
+        }
+
+        self.hurt(amount, Some(source), weapon);
+    }
+
+    #[func]
+    fn destroy(&mut self) {
+        self.destroy_by(None, None);
+    }
+
+    fn hurt(&mut self, amount: f32, killer: Option<Gd<Node3D>>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
+        // Getting shot gives away roughly where the shooter is
+        if let Some(position) = self.player.as_ref().map(|x| x.get_position()) {
+            self.awareness.notice(position);
+        }
+
+        let sound_hurt = self.stats.sound_hurt.to_string();
+        self.play_sound(&sound_hurt);
+
+        if self.reaction.hit(amount, &self.stats) {
+            self.base().get_node_as::<Timer>("Timer").stop();
+        }
+
+        self.health -= amount;
+        if self.health < 0.0 {
+            self.destroy_by(killer, weapon);
+        }
+    }
+
+    // Pops up and topples over, bursting once `death_delay` runs out
+    fn destroy_by(&mut self, killer: Option<Gd<Node3D>>, weapon: Option<Gd<Weapon>>) {
+        if self.destroyed {
+            return;
+        }
+
+        self.destroyed = true;
+        self.base_mut().remove_from_group("enemy".into());
+        self.reaction.clear();
+        self.base().get_node_as::<Timer>("Timer").stop();
+
+        let angle = rand::thread_rng().gen_range(0.0..std::f32::consts::TAU);
+        self.spin_axis = Vector3::new(angle.cos(), 0.0, angle.sin());
+        self.gravity = -5.0;
+        self.jumping = false;
+
+        enemy_common::emit_destroyed(&mut self.base().clone().upcast(), killer, weapon);
+    }
+
+    #[func]
+    fn _on_noise_made(&mut self, position: Vector3, radius: f32) {
+        if self.destroyed {
+            return;
+        }
+
+        let (hearing, listener) = (self.stats.hearing, self.base().get_position());
+        self.awareness.hear(position, radius, hearing, listener);
+    }
+
+    #[func]
+    fn _on_navigation_baked(&mut self) {
+        self.navigation_ready = true;
+        self.repath = true;
+    }
+
+    // Jumps across navigation links, taking a ballistic arc that lands on the
+    // link's exit under the same gravity as the player
+    #[func]
+    fn _on_link_reached(&mut self, details: Dictionary) {
+        let Some(exit) = details
+            .get("link_exit_position")
+            .and_then(|x| x.try_to::<Vector3>().ok())
+        else {
+            return;
+        };
+
+        let position = self.base().get_global_position();
+        let mut offset = exit - position;
+        let rise = offset.y;
+        offset.y = 0.0;
+
+        let time = (offset.length() / self.stats.move_speed.max(0.1)).max(0.4);
+        let velocity = offset / time;
+        self.gravity = -(rise + 10.0 * time * time) / time;
+        self.jumping = true;
+
+        self.base_mut()
+            .set_velocity(Vector3::new(velocity.x, -self.gravity, velocity.z));
+    }
+
+    #[func]
+    fn _on_timer_timeout(&mut self) {
+        if self.destroyed || self.awareness.state != EnemyState::Attack {
+            return;
+        }
+
+        let shooter = self.base().clone().upcast::<Node3D>();
+        if self.stats.projectile.is_none() {
+            if enemy_common::fire_hitscan(&shooter, &self.stats, &mut self.raycast) {
+                self.play_muzzle();
+            }
+            return;
+        }
+
+        let Some(player) = self.player.clone() else {
+            return;
+        };
+        self.play_muzzle();
+        let origin = self.muzzle.get_global_position();
+        enemy_common::fire_projectile(&shooter, &self.stats, &player, origin);
+    }
+
+    // Picks where to head for in the current state, if anywhere
+    fn decide(&mut self, delta: f64) -> Option<Vector3> {
+        let senses = self.sense(delta);
+        if let Some(previous) = self
+            .awareness
+            .advance(&senses, &self.stats.thresholds(), delta)
+        {
+            let state = self.awareness.state;
+            enemy_common::emit_state_changed(&mut self.base().clone().upcast(), previous, state);
+        }
+
+        let position = self.base().get_position();
+        let player_position = self.player.as_ref().map(|x| x.get_position());
+        match self.awareness.state {
+            EnemyState::Idle | EnemyState::Alert | EnemyState::Attack => None,
+            EnemyState::Patrol => Some(self.patrol()),
+            EnemyState::Chase => {
+                player_position.filter(|_| senses.distance > self.stats.engagement_distance)
+            }
+            EnemyState::Investigate => self.awareness.last_known_position,
+            EnemyState::Flee => player_position.map(|x| {
+                let mut away = position - x;
+                away.y = 0.0;
+                position + away.normalized() * 5.0
+            }),
+        }
+    }
+
+    fn sense(&mut self, delta: f64) -> Senses {
+        let forward = self.base().get_global_transform().basis.col_c();
+        let engaged = self.awareness.state.is_engaged();
+        let can_see = self.player.as_ref().is_some_and(|player| {
+            enemy_common::can_see_player(player, &mut self.sight, forward, &self.stats, engaged)
+        });
+        let position = self.base().get_position();
+        let player_position = self.player.as_ref().map(|x| x.get_position());
+
+        let mut senses = Senses {
+            health: self.health / self.stats.max_health.max(1.0),
+            can_patrol: !self.patrol_points.is_empty(),
+            ..self
+                .awareness
+                .sense(delta, position, player_position, can_see, 1.0)
+        };
+        senses.reached_lead |=
+            self.awareness.state == EnemyState::Investigate && self.agent.is_navigation_finished();
+        senses
+    }
+
+    fn patrol(&mut self) -> Vector3 {
+        let position = self.base().get_position();
+        enemy_common::patrol(
+            self.origin,
+            &self.patrol_points,
+            &mut self.patrol_index,
+            position,
+        )
+    }
+
+    fn topple(&mut self, delta: f64) {
+        self.death_time += delta;
+        if self.death_time >= self.stats.death_delay {
+            self.explode();
+            return;
+        }
+
+        self.gravity += 20.0 * delta as f32;
+        let mut velocity = self.base().get_velocity();
+        velocity.x *= 0.9;
+        velocity.z *= 0.9;
+        velocity.y = -self.gravity;
+        self.base_mut().set_velocity(velocity);
+        self.base_mut().move_and_slide();
+
+        // Tips over until lying on its side
+        if self.death_time * (self.stats.death_spin as f64) < std::f64::consts::FRAC_PI_2 {
+            let (axis, angle) = (self.spin_axis, self.stats.death_spin * delta as f32);
+            self.base_mut().rotate(axis, angle);
+        }
+    }
+
+    fn explode(&mut self) {
+        let position = self.base().get_global_position() + Vector3::new(0.0, 0.5, 0.0);
+        let mut enemy = self.base().clone().upcast::<Node3D>();
+        let player = self.player.as_ref();
+        enemy_common::explode(&mut enemy, &self.stats, player, position, position);
+    }
+
+    // Turns around the vertical axis only, so the body stays upright
+    fn face(&mut self, position: Vector3) {
+        let own = self.base().get_position();
+        let target = Vector3::new(position.x, own.y, position.z);
+        if target.distance_to(own) < 0.01 {
+            return;
+        }
+
+        self.base_mut()
+            .look_at_ex(target)
+            .use_model_front(true)
+            .done();
+    }
+
+    fn play_muzzle(&mut self) {
+        self.muzzle.set_frame(0);
+        self.muzzle.play_ex().name("default".into()).done();
+        let mut degrees = self.muzzle.get_rotation_degrees();
+        degrees.z = rand::thread_rng().gen_range(-45.0..45.0);
+        self.muzzle.set_rotation_degrees(degrees);
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+
+    #[signal]
+    fn state_changed(from: GString, to: GString);
+
+    #[signal]
+    fn enemy_destroyed(enemy: Gd<Node3D>, killer: Gd<Node3D>, weapon: Gd<Weapon>);
+}
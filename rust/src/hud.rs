@@ -21,4 +21,32 @@ impl HUD {
         let mut health_text_node = self.base().get_node_as::<Label>("Health");
         health_text_node.set_text(format!("{}%", health).into());
     }
+
+    #[func]
+    fn _on_armor_updated(&mut self, armor: i32) {
+        let mut armor_text_node = self.base().get_node_as::<Label>("Armor");
+        armor_text_node.set_visible(armor > 0);
+        armor_text_node.set_text(format!("{}", armor).into());
+    }
+
+    #[func]
+    fn _on_ammo_updated(&mut self, ammo: i32) {
+        let mut ammo_text_node = self.base().get_node_as::<Label>("Ammo");
+        ammo_text_node.set_visible(ammo >= 0);
+        ammo_text_node.set_text(format!("{}", ammo).into());
+    }
+
+    #[func]
+    fn _on_wave_started(&mut self, wave: i32) {
+        let mut wave_text_node = self.base().get_node_as::<Label>("Wave");
+        wave_text_node.show();
+        wave_text_node.set_text(format!("Wave {}", wave).into());
+    }
+
+    #[func]
+    fn _on_survival_ended(&mut self, waves_survived: i32) {
+        let mut death_text_node = self.base().get_node_as::<Label>("DeathScreen");
+        death_text_node.show();
+        death_text_node.set_text(format!("Survived {} waves", waves_survived).into());
+    }
 }
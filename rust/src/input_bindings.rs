@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+
+use godot::engine::global::{Error, Key};
+use godot::engine::node::ProcessMode;
+use godot::engine::{
+    ConfigFile, INode, InputEvent, InputEventJoypadButton, InputEventJoypadMotion, InputEventKey,
+    InputEventMouseButton, InputMap,
+};
+use godot::prelude::*;
+
+const BINDINGS_PATH: &str = "user://input.cfg";
+const BINDINGS_SECTION: &str = "input";
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct InputBindingsBase {
+    defaults: HashMap<String, Array<Gd<InputEvent>>>,
+    overridden: HashSet<String>,
+    capturing: Option<StringName>,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for InputBindingsBase {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            defaults: HashMap::new(),
+            overridden: HashSet::new(),
+            capturing: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        // Rebinding happens from the pause menu, while the tree is paused
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+
+        let mut input_map = InputMap::singleton();
+        for action in self.list_actions().iter_shared() {
+            let events = input_map.action_get_events(action.clone());
+            self.defaults.insert(action.to_string(), events);
+        }
+
+        self.load();
+    }
+
+    fn input(&mut self, event: Gd<InputEvent>) {
+        let Some(action) = self.capturing.clone() else {
+            return;
+        };
+        if !Self::is_bindable(&event) {
+            return;
+        }
+
+        self.base().get_viewport().unwrap().set_input_as_handled();
+        self.capturing = None;
+
+        if let Ok(key) = event.clone().try_cast::<InputEventKey>() {
+            if key.get_physical_keycode() == Key::ESCAPE {
+                self.emit_deferred("capture_cancelled", &[Variant::from(action)]);
+                return;
+            }
+        }
+
+        self.rebind(action, event, false);
+    }
+}
+
+#[godot_api]
+impl InputBindingsBase {
+    #[func]
+    fn list_actions(&self) -> Array<StringName> {
+        let mut actions = Array::new();
+        for action in InputMap::singleton().get_actions().iter_shared() {
+            if !action.to_string().starts_with("ui_") {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+
+    #[func]
+    fn start_capture(&mut self, action: StringName) {
+        self.capturing = Some(action);
+    }
+
+    #[func]
+    fn cancel_capture(&mut self) {
+        if let Some(action) = self.capturing.take() {
+            self.emit_deferred("capture_cancelled", &[Variant::from(action)]);
+        }
+    }
+
+    #[func]
+    fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    // Returns the first other action already bound to `event`, or an empty name
+    #[func]
+    fn find_conflict(&self, action: StringName, event: Gd<InputEvent>) -> StringName {
+        let mut input_map = InputMap::singleton();
+        for other in self.list_actions().iter_shared() {
+            if other == action {
+                continue;
+            }
+
+            for bound in input_map.action_get_events(other.clone()).iter_shared() {
+                if bound.is_match(event.clone()) {
+                    return other;
+                }
+            }
+        }
+
+        StringName::default()
+    }
+
+    // Binds `event` to `action`, replacing its binding for the same kind of
+    // device. A conflicting binding is only stolen when `replace_conflicts` is set.
+    #[func]
+    fn rebind(
+        &mut self,
+        action: StringName,
+        event: Gd<InputEvent>,
+        replace_conflicts: bool,
+    ) -> bool {
+        let conflict = self.find_conflict(action.clone(), event.clone());
+        if !conflict.is_empty() {
+            if !replace_conflicts {
+                self.emit_deferred(
+                    "binding_conflict",
+                    &[
+                        Variant::from(action),
+                        Variant::from(conflict),
+                        Variant::from(event),
+                    ],
+                );
+                return false;
+            }
+
+            self.erase_matching(conflict.clone(), &event);
+            self.overridden.insert(conflict.to_string());
+        }
+
+        let mut input_map = InputMap::singleton();
+        for bound in input_map.action_get_events(action.clone()).iter_shared() {
+            if Self::is_joypad(&bound) == Self::is_joypad(&event) {
+                input_map.action_erase_event(action.clone(), bound);
+            }
+        }
+        input_map.action_add_event(action.clone(), event);
+        self.overridden.insert(action.to_string());
+
+        self.save();
+        self.emit_deferred("bindings_changed", &[]);
+
+        true
+    }
+
+    #[func]
+    fn reset_action(&mut self, action: StringName) {
+        self.restore_default(&action);
+        self.overridden.remove(&action.to_string());
+
+        self.save();
+        self.emit_deferred("bindings_changed", &[]);
+    }
+
+    #[func]
+    fn reset_all(&mut self) {
+        for action in self.list_actions().iter_shared() {
+            self.restore_default(&action);
+        }
+        self.overridden.clear();
+
+        self.save();
+        self.emit_deferred("bindings_changed", &[]);
+    }
+
+    // Listeners commonly call back into this node, which must not happen while
+    // it is still borrowed by the emitting method
+    fn emit_deferred(&mut self, signal: &str, args: &[Variant]) {
+        let mut call_args = vec![Variant::from(StringName::from(signal))];
+        call_args.extend_from_slice(args);
+        self.base_mut()
+            .call_deferred("emit_signal".into(), &call_args);
+    }
+
+    fn restore_default(&self, action: &StringName) {
+        let Some(events) = self.defaults.get(&action.to_string()) else {
+            return;
+        };
+
+        let mut input_map = InputMap::singleton();
+        input_map.action_erase_events(action.clone());
+        for event in events.iter_shared() {
+            input_map.action_add_event(action.clone(), event);
+        }
+    }
+
+    fn erase_matching(&self, action: StringName, event: &Gd<InputEvent>) {
+        let mut input_map = InputMap::singleton();
+        for bound in input_map.action_get_events(action.clone()).iter_shared() {
+            if bound.is_match(event.clone()) {
+                input_map.action_erase_event(action.clone(), bound);
+            }
+        }
+    }
+
+    fn load(&mut self) {
+        let mut config = ConfigFile::new_gd();
+        if config.load(BINDINGS_PATH.into()) != Error::OK {
+            return;
+        }
+
+        let mut input_map = InputMap::singleton();
+        for action in self.list_actions().iter_shared() {
+            let key = GString::from(action.to_string());
+            if !config.has_section_key(BINDINGS_SECTION.into(), key.clone()) {
+                continue;
+            }
+
+            let Ok(events) = config
+                .get_value(BINDINGS_SECTION.into(), key)
+                .try_to::<VariantArray>()
+            else {
+                continue;
+            };
+
+            input_map.action_erase_events(action.clone());
+            for event in events.iter_shared() {
+                if let Ok(event) = event.try_to::<Gd<InputEvent>>() {
+                    input_map.action_add_event(action.clone(), event);
+                }
+            }
+            self.overridden.insert(action.to_string());
+        }
+    }
+
+    fn save(&self) {
+        let mut config = ConfigFile::new_gd();
+        let mut input_map = InputMap::singleton();
+        for action in &self.overridden {
+            let events = input_map.action_get_events(action.as_str().into());
+            config.set_value(
+                BINDINGS_SECTION.into(),
+                action.as_str().into(),
+                Variant::from(events),
+            );
+        }
+
+        if config.save(BINDINGS_PATH.into()) != Error::OK {
+            godot_error!("Failed to save input bindings to {}", BINDINGS_PATH);
+        }
+    }
+
+    fn is_bindable(event: &Gd<InputEvent>) -> bool {
+        if let Ok(motion) = event.clone().try_cast::<InputEventJoypadMotion>() {
+            return motion.get_axis_value().abs() > 0.5;
+        }
+
+        event.is_pressed()
+            && (event.clone().try_cast::<InputEventKey>().is_ok()
+                || event.clone().try_cast::<InputEventMouseButton>().is_ok()
+                || event.clone().try_cast::<InputEventJoypadButton>().is_ok())
+    }
+
+    fn is_joypad(event: &Gd<InputEvent>) -> bool {
+        event.clone().try_cast::<InputEventJoypadButton>().is_ok()
+            || event.clone().try_cast::<InputEventJoypadMotion>().is_ok()
+    }
+
+    #[signal]
+    fn binding_conflict(action: StringName, conflicting_action: StringName, event: Gd<InputEvent>);
+
+    #[signal]
+    fn capture_cancelled(action: StringName);
+
+    #[signal]
+    fn bindings_changed();
+}
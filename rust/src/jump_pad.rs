@@ -0,0 +1,107 @@
+use godot::engine::mesh::PrimitiveType;
+use godot::engine::{Area3D, Engine, IArea3D, ImmediateMesh, MeshInstance3D, Node3D};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(tool, base=Area3D)]
+pub struct JumpPad {
+    #[export]
+    impulse: Vector3,
+
+    #[export]
+    sound_launch: GString,
+
+    trajectory: Option<Gd<ImmediateMesh>>,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for JumpPad {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            impulse: Vector3::new(0.0, 14.0, 0.0),
+
+            sound_launch: GString::from("sounds/jump_a.ogg"),
+
+            trajectory: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        if Engine::singleton().is_editor_hint() {
+            let trajectory = create_trajectory(&mut self.base_mut());
+            self.trajectory = Some(trajectory);
+            return;
+        }
+
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+    }
+
+    fn process(&mut self, _delta: f64) {
+        if let Some(trajectory) = self.trajectory.as_mut() {
+            let transform = self.base().get_global_transform();
+            update_trajectory(trajectory, transform.origin, transform.basis * self.impulse);
+        }
+    }
+}
+
+#[godot_api]
+impl JumpPad {
+    #[func]
+    fn _on_body_entered(&mut self, mut body: Gd<Node3D>) {
+        if !body.has_method("launch".into()) {
+            return;
+        }
+
+        let velocity = self.base().get_global_transform().basis * self.impulse;
+        body.call("launch".into(), &[Variant::from(velocity)]);
+
+        let sound_launch = self.sound_launch.to_string();
+        self.play_sound(&sound_launch);
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+}
+
+// Adds an editor-only line mesh under `parent` for previewing launch arcs
+pub fn create_trajectory(parent: &mut Node3D) -> Gd<ImmediateMesh> {
+    let mesh = ImmediateMesh::new_gd();
+
+    let mut instance = MeshInstance3D::new_alloc();
+    instance.set_as_top_level(true);
+    instance.set_mesh(mesh.clone().upcast());
+    parent.add_child(instance.upcast());
+
+    mesh
+}
+
+// Rebuilds `mesh` with the path a launched Player follows, using the same
+// gravity and air control as `Player::physics_process`
+pub fn update_trajectory(mesh: &mut Gd<ImmediateMesh>, origin: Vector3, mut velocity: Vector3) {
+    mesh.clear_surfaces();
+    mesh.surface_begin(PrimitiveType::LINE_STRIP);
+
+    let delta = 1.0 / 30.0;
+    let mut position = origin;
+    for _ in 0..120 {
+        mesh.surface_add_vertex(position);
+
+        velocity.x *= 1.0 - delta;
+        velocity.z *= 1.0 - delta;
+        velocity.y -= 20.0 * delta;
+        position += velocity * delta;
+
+        if position.y < origin.y - 5.0 {
+            break;
+        }
+    }
+
+    mesh.surface_end();
+}
@@ -0,0 +1,60 @@
+use godot::engine::{INavigationRegion3D, NavigationRegion3D};
+use godot::prelude::*;
+
+// Bakes its navigation mesh from the level when the scene starts, so edits to
+// the level never leave a stale mesh behind. Ground enemies hold still until
+// `bake_finished` fires.
+#[derive(GodotClass)]
+#[class(base=NavigationRegion3D)]
+pub struct LevelNavigation {
+    #[export]
+    bake_on_ready: bool,
+
+    baked: bool,
+
+    base: Base<NavigationRegion3D>,
+}
+
+#[godot_api]
+impl INavigationRegion3D for LevelNavigation {
+    fn init(base: Base<NavigationRegion3D>) -> Self {
+        Self {
+            bake_on_ready: true,
+
+            baked: false,
+
+            base,
+        }
+    }
+
+    fn enter_tree(&mut self) {
+        self.base_mut().add_to_group("level_navigation".into());
+    }
+
+    fn ready(&mut self) {
+        if !self.bake_on_ready {
+            self.baked = true;
+            return;
+        }
+
+        let callable = self.base().callable("_on_bake_finished");
+        self.base_mut().connect("bake_finished".into(), callable);
+        self.base_mut()
+            .bake_navigation_mesh_ex()
+            .on_thread(true)
+            .done();
+    }
+}
+
+#[godot_api]
+impl LevelNavigation {
+    #[func]
+    fn is_baked(&self) -> bool {
+        self.baked
+    }
+
+    #[func]
+    fn _on_bake_finished(&mut self) {
+        self.baked = true;
+    }
+}
@@ -1,8 +1,32 @@
 mod audio;
+mod boost_zone;
+mod camera_shake;
+mod checkpoint;
+mod climb_volume;
 mod enemy;
+mod enemy_archetype;
+mod enemy_common;
+mod enemy_spawner;
+mod enemy_squad;
+mod enemy_state;
+mod enemy_wave;
+mod falling_platform;
+mod ground_enemy;
 mod hud;
 mod impact;
+mod input_bindings;
+mod jump_pad;
+mod level_navigation;
+mod loot_drop;
+mod moving_platform;
+mod pause_menu;
+mod pickup;
 mod player;
+mod projectile;
+mod respawn;
+mod settings;
+mod surface;
+mod water_volume;
 mod weapon;
 
 use godot::prelude::*;
@@ -0,0 +1,58 @@
+use godot::engine::Resource;
+use godot::prelude::*;
+use rand::Rng;
+
+#[derive(GodotClass)]
+#[class(base=Resource)]
+pub struct LootDrop {
+    #[export]
+    scene: Option<Gd<PackedScene>>,
+    #[export(range = (0.0, 1.0))]
+    chance: f32,
+    #[export(range = (1.0, 10.0))]
+    count: i32,
+
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl IResource for LootDrop {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            scene: None,
+            chance: 0.5,
+            count: 1,
+
+            base,
+        }
+    }
+}
+
+// Rolls every entry of `loot` and scatters whatever drops around `position`
+pub fn drop_loot(loot: &Array<Gd<LootDrop>>, parent: &mut Gd<Node>, position: Vector3) {
+    let mut rng = rand::thread_rng();
+    for drop in loot.iter_shared() {
+        let Ok(scene) = drop.get("scene".into()).try_to::<Gd<PackedScene>>() else {
+            continue;
+        };
+        let chance = drop.get("chance".into()).to::<f32>();
+        let count = drop.get("count".into()).to::<i32>();
+
+        for _ in 0..count {
+            if rng.gen::<f32>() >= chance {
+                continue;
+            }
+
+            let Some(mut item) = scene
+                .instantiate()
+                .and_then(|x| x.try_cast::<Node3D>().ok())
+            else {
+                continue;
+            };
+
+            let offset = Vector3::new(rng.gen_range(-0.75..0.75), 0.0, rng.gen_range(-0.75..0.75));
+            item.set_position(position + offset);
+            parent.add_child(item.upcast());
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use godot::engine::utilities::ease;
+use godot::engine::{AnimatableBody3D, IAnimatableBody3D};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=AnimatableBody3D)]
+pub struct MovingPlatform {
+    #[export]
+    waypoints: PackedVector3Array,
+    #[export(range = (0.1, 20.0))]
+    speed: f32,
+    #[export(range = (0.0, 10.0))]
+    wait_time: f64,
+    #[export(range = (-4.0, 4.0))]
+    easing: f64,
+
+    origin: Vector3,
+    waypoint_index: usize,
+    progress: f32,
+    waiting: f64,
+
+    base: Base<AnimatableBody3D>,
+}
+
+#[godot_api]
+impl IAnimatableBody3D for MovingPlatform {
+    fn init(base: Base<AnimatableBody3D>) -> Self {
+        Self {
+            waypoints: PackedVector3Array::new(),
+            speed: 2.0,
+            wait_time: 1.0,
+            easing: -2.0,
+
+            origin: Vector3::ZERO,
+            waypoint_index: 0,
+            progress: 0.0,
+            waiting: 0.0,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.origin = self.base().get_position();
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        if self.waypoints.len() < 2 {
+            return;
+        }
+
+        if self.waiting > 0.0 {
+            self.waiting -= delta;
+            return;
+        }
+
+        let from = self.waypoints.get(self.waypoint_index);
+        let to = self
+            .waypoints
+            .get((self.waypoint_index + 1) % self.waypoints.len());
+
+        let distance = from.distance_to(to).max(0.01);
+        self.progress = (self.progress + self.speed * delta as f32 / distance).min(1.0);
+
+        let weight = ease(self.progress as f64, self.easing) as f32;
+        let position = self.origin + from.lerp(to, weight);
+        self.base_mut().set_position(position);
+
+        if self.progress >= 1.0 {
+            self.progress = 0.0;
+            self.waypoint_index = (self.waypoint_index + 1) % self.waypoints.len();
+            self.waiting = self.wait_time;
+        }
+    }
+}
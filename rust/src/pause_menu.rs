@@ -0,0 +1,434 @@
+use godot::engine::control::LayoutPreset;
+use godot::engine::input::MouseMode;
+use godot::engine::node::ProcessMode;
+use godot::engine::{
+    Button, CanvasLayer, CenterContainer, CheckBox, ColorRect, Control, HBoxContainer, HSlider,
+    ICanvasLayer, InputEvent, InputMap, Label, ScrollContainer, VBoxContainer,
+};
+use godot::prelude::*;
+
+// Key, label, min, max and step of each slider on the settings screen
+const SLIDERS: [(&str, &str, f64, f64, f64); 7] = [
+    (
+        "mouse_sensitivity",
+        "Mouse sensitivity",
+        100.0,
+        2000.0,
+        10.0,
+    ),
+    (
+        "gamepad_sensitivity",
+        "Gamepad sensitivity",
+        0.01,
+        0.25,
+        0.005,
+    ),
+    ("fov", "Field of view", 60.0, 110.0, 1.0),
+    ("master_volume", "Master volume", 0.0, 1.0, 0.05),
+    ("sfx_volume", "Effects volume", 0.0, 1.0, 0.05),
+    ("music_volume", "Music volume", 0.0, 1.0, 0.05),
+    ("shake_scale", "Camera shake", 0.0, 2.0, 0.1),
+];
+
+const TOGGLES: [(&str, &str); 2] = [
+    ("invert_y", "Invert Y"),
+    ("reduced_motion", "Reduced motion"),
+];
+
+#[derive(GodotClass)]
+#[class(base=CanvasLayer)]
+pub struct PauseMenu {
+    #[export]
+    player: Option<Gd<Node3D>>,
+
+    main_panel: OnReady<Gd<VBoxContainer>>,
+    settings_panel: OnReady<Gd<VBoxContainer>>,
+    controls_panel: OnReady<Gd<VBoxContainer>>,
+    resume_button: OnReady<Gd<Button>>,
+    game_over: bool,
+
+    binding_buttons: Vec<(StringName, Gd<Button>)>,
+    binding_status: OnReady<Gd<Label>>,
+    replace_button: OnReady<Gd<Button>>,
+    pending_conflict: Option<(StringName, Gd<InputEvent>)>,
+
+    base: Base<CanvasLayer>,
+}
+
+#[godot_api]
+impl ICanvasLayer for PauseMenu {
+    fn init(base: Base<CanvasLayer>) -> Self {
+        Self {
+            player: None,
+
+            main_panel: OnReady::manual(),
+            settings_panel: OnReady::manual(),
+            controls_panel: OnReady::manual(),
+            resume_button: OnReady::manual(),
+            game_over: false,
+
+            binding_buttons: Vec::new(),
+            binding_status: OnReady::manual(),
+            replace_button: OnReady::manual(),
+            pending_conflict: None,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        // The menu has to keep running while the rest of the tree is paused
+        self.base_mut().set_process_mode(ProcessMode::ALWAYS);
+        self.base_mut().set_layer(10);
+
+        let mut background = ColorRect::new_alloc();
+        background.set_color(Color::from_rgba(0.0, 0.0, 0.0, 0.6));
+        background.set_anchors_preset(LayoutPreset::FULL_RECT);
+        self.base_mut().add_child(background.upcast());
+
+        let mut center = CenterContainer::new_alloc();
+        center.set_anchors_preset(LayoutPreset::FULL_RECT);
+
+        let mut main_panel = VBoxContainer::new_alloc();
+        let resume_button = self.add_button(&mut main_panel, "Resume", "_on_resume_pressed");
+        self.add_button(&mut main_panel, "Settings", "_on_settings_pressed");
+        self.add_button(&mut main_panel, "Controls", "_on_controls_pressed");
+        self.add_button(&mut main_panel, "Restart", "_on_restart_pressed");
+        self.add_button(&mut main_panel, "Quit", "_on_quit_pressed");
+        center.add_child(main_panel.clone().upcast());
+
+        let mut settings_panel = VBoxContainer::new_alloc();
+        self.build_settings(&mut settings_panel);
+        self.add_button(&mut settings_panel, "Back", "_on_back_pressed");
+        settings_panel.hide();
+        center.add_child(settings_panel.clone().upcast());
+
+        let mut controls_panel = VBoxContainer::new_alloc();
+        self.build_controls(&mut controls_panel);
+        controls_panel.hide();
+        center.add_child(controls_panel.clone().upcast());
+
+        self.base_mut().add_child(center.upcast());
+        self.main_panel.init(main_panel);
+        self.settings_panel.init(settings_panel);
+        self.controls_panel.init(controls_panel);
+        self.resume_button.init(resume_button);
+        self.update_bindings();
+
+        let mut input_bindings = self.base().get_node_as::<Node>("/root/InputBindings");
+        for (signal, method) in [
+            ("bindings_changed", "_on_bindings_changed"),
+            ("capture_cancelled", "_on_capture_cancelled"),
+            ("binding_conflict", "_on_binding_conflict"),
+        ] {
+            input_bindings.connect(signal.into(), self.base().callable(method));
+        }
+
+        self.base_mut().hide();
+    }
+
+    // Unhandled input only, so the Escape that cancels a rebind, which
+    // InputBindings marks as handled, doesn't also toggle the menu
+    fn unhandled_input(&mut self, event: Gd<InputEvent>) {
+        if self.game_over || !event.is_action_pressed("mouse_capture_exit".into()) {
+            return;
+        }
+
+        self.base().get_viewport().unwrap().set_input_as_handled();
+        if self.base().get_tree().unwrap().is_paused() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+}
+
+#[godot_api]
+impl PauseMenu {
+    #[func]
+    fn pause(&mut self) {
+        self.base().get_tree().unwrap().set_pause(true);
+        Input::singleton().set_mouse_mode(MouseMode::VISIBLE);
+
+        self.main_panel.show();
+        self.settings_panel.hide();
+        self.controls_panel.hide();
+        self.base_mut().show();
+    }
+
+    #[func]
+    fn resume(&mut self) {
+        self.base_mut().hide();
+        self.base().get_tree().unwrap().set_pause(false);
+
+        if let Some(player) = self.player.as_mut() {
+            player.call("capture_mouse".into(), &[]);
+        }
+    }
+
+    #[func]
+    fn _on_resume_pressed(&mut self) {
+        self.resume();
+    }
+
+    #[func]
+    fn _on_settings_pressed(&mut self) {
+        self.main_panel.hide();
+        self.settings_panel.show();
+    }
+
+    #[func]
+    fn _on_back_pressed(&mut self) {
+        let mut settings = self.base().get_node_as::<Node>("/root/Settings");
+        settings.call("save".into(), &[]);
+
+        self.settings_panel.hide();
+        self.main_panel.show();
+    }
+
+    #[func]
+    fn _on_controls_pressed(&mut self) {
+        self.main_panel.hide();
+        self.controls_panel.show();
+    }
+
+    #[func]
+    fn _on_controls_back_pressed(&mut self) {
+        self.input_bindings_call("cancel_capture", &[]);
+        self.clear_conflict();
+
+        self.controls_panel.hide();
+        self.main_panel.show();
+    }
+
+    // Waits for the next key, button or axis, or Escape to cancel
+    #[func]
+    fn _on_binding_pressed(&mut self, action: StringName) {
+        self.clear_conflict();
+
+        if let Some((_, button)) = self.binding_buttons.iter_mut().find(|(x, _)| *x == action) {
+            button.set_text("Press a key...".into());
+        }
+        self.input_bindings_call("start_capture", &[Variant::from(action)]);
+    }
+
+    #[func]
+    fn _on_replace_pressed(&mut self) {
+        let Some((action, event)) = self.pending_conflict.take() else {
+            return;
+        };
+
+        self.input_bindings_call(
+            "rebind",
+            &[
+                Variant::from(action),
+                Variant::from(event),
+                Variant::from(true),
+            ],
+        );
+    }
+
+    #[func]
+    fn _on_reset_bindings_pressed(&mut self) {
+        self.input_bindings_call("reset_all", &[]);
+    }
+
+    #[func]
+    fn _on_bindings_changed(&mut self) {
+        self.clear_conflict();
+        self.update_bindings();
+    }
+
+    #[func]
+    fn _on_capture_cancelled(&mut self, _action: StringName) {
+        self.update_bindings();
+    }
+
+    #[func]
+    fn _on_binding_conflict(
+        &mut self,
+        action: StringName,
+        conflicting_action: StringName,
+        event: Gd<InputEvent>,
+    ) {
+        self.binding_status.set_text(
+            format!(
+                "{} is already bound to {}",
+                event.as_text(),
+                conflicting_action
+            )
+            .into(),
+        );
+        self.replace_button.show();
+        self.pending_conflict = Some((action, event));
+        self.update_bindings();
+    }
+
+    // A finished survival run can only be restarted or quit
+    #[func]
+    fn _on_survival_ended(&mut self, _waves_survived: i32) {
+        self.game_over = true;
+        self.pause();
+        self.resume_button.hide();
+    }
+
+    #[func]
+    fn _on_restart_pressed(&mut self) {
+        let mut tree = self.base().get_tree().unwrap();
+        tree.set_pause(false);
+        tree.reload_current_scene();
+    }
+
+    #[func]
+    fn _on_quit_pressed(&mut self) {
+        self.base().get_tree().unwrap().quit();
+    }
+
+    #[func]
+    fn _on_slider_changed(&mut self, value: f64, key: GString) {
+        self.set_setting(key, Variant::from(value as f32));
+    }
+
+    #[func]
+    fn _on_toggle_changed(&mut self, pressed: bool, key: GString) {
+        self.set_setting(key, Variant::from(pressed));
+    }
+
+    fn set_setting(&self, key: GString, value: Variant) {
+        let mut settings = self.base().get_node_as::<Node>("/root/Settings");
+        settings.call("set_setting".into(), &[Variant::from(key), value]);
+    }
+
+    fn build_settings(&self, panel: &mut Gd<VBoxContainer>) {
+        let mut settings = self.base().get_node_as::<Node>("/root/Settings");
+
+        for (key, text, min, max, step) in SLIDERS {
+            let value = settings
+                .call("get_setting".into(), &[Variant::from(GString::from(key))])
+                .to::<f64>();
+
+            let mut slider = HSlider::new_alloc();
+            slider.set_min(min);
+            slider.set_max(max);
+            slider.set_step(step);
+            slider.set_value(value);
+            slider.set_custom_minimum_size(Vector2::new(240.0, 0.0));
+            slider.connect(
+                "value_changed".into(),
+                self.base()
+                    .callable("_on_slider_changed")
+                    .bindv((&[Variant::from(GString::from(key))]).into()),
+            );
+
+            Self::add_row(panel, text, slider.upcast());
+        }
+
+        for (key, text) in TOGGLES {
+            let pressed = settings
+                .call("get_setting".into(), &[Variant::from(GString::from(key))])
+                .to::<bool>();
+
+            let mut check_box = CheckBox::new_alloc();
+            check_box.set_pressed_no_signal(pressed);
+            check_box.connect(
+                "toggled".into(),
+                self.base()
+                    .callable("_on_toggle_changed")
+                    .bindv((&[Variant::from(GString::from(key))]).into()),
+            );
+
+            Self::add_row(panel, text, check_box.upcast());
+        }
+    }
+
+    fn build_controls(&mut self, panel: &mut Gd<VBoxContainer>) {
+        let actions = self
+            .input_bindings_call("list_actions", &[])
+            .to::<Array<StringName>>();
+
+        // There are more actions than fit on screen at once
+        let mut scroll = ScrollContainer::new_alloc();
+        scroll.set_custom_minimum_size(Vector2::new(480.0, 360.0));
+        let mut rows = VBoxContainer::new_alloc();
+
+        for action in actions.iter_shared() {
+            let mut button = Button::new_alloc();
+            button.set_custom_minimum_size(Vector2::new(240.0, 0.0));
+            button.connect(
+                "pressed".into(),
+                self.base()
+                    .callable("_on_binding_pressed")
+                    .bindv((&[Variant::from(action.clone())]).into()),
+            );
+
+            Self::add_row(&mut rows, &action.to_string(), button.clone().upcast());
+            self.binding_buttons.push((action, button));
+        }
+
+        scroll.add_child(rows.upcast());
+        panel.add_child(scroll.upcast());
+
+        let status = Label::new_alloc();
+        panel.add_child(status.clone().upcast());
+        self.binding_status.init(status);
+
+        let mut replace_button = Button::new_alloc();
+        replace_button.set_text("Replace".into());
+        replace_button.connect(
+            "pressed".into(),
+            self.base().callable("_on_replace_pressed"),
+        );
+        replace_button.hide();
+        panel.add_child(replace_button.clone().upcast());
+        self.replace_button.init(replace_button);
+
+        self.add_button(panel, "Reset to defaults", "_on_reset_bindings_pressed");
+        self.add_button(panel, "Back", "_on_controls_back_pressed");
+    }
+
+    // Shows every event bound to each action, keyboard and gamepad alike
+    fn update_bindings(&mut self) {
+        let mut input_map = InputMap::singleton();
+        for (action, button) in self.binding_buttons.iter_mut() {
+            let events = input_map
+                .action_get_events(action.clone())
+                .iter_shared()
+                .map(|x| x.as_text().to_string())
+                .collect::<Vec<_>>();
+            button.set_text(events.join(", ").into());
+        }
+    }
+
+    fn clear_conflict(&mut self) {
+        self.pending_conflict = None;
+        self.binding_status.set_text("".into());
+        self.replace_button.hide();
+    }
+
+    fn input_bindings_call(&self, method: &str, args: &[Variant]) -> Variant {
+        let mut input_bindings = self.base().get_node_as::<Node>("/root/InputBindings");
+        input_bindings.call(method.into(), args)
+    }
+
+    fn add_row(panel: &mut Gd<VBoxContainer>, text: &str, control: Gd<Control>) {
+        let mut row = HBoxContainer::new_alloc();
+
+        let mut label = Label::new_alloc();
+        label.set_text(text.into());
+        label.set_custom_minimum_size(Vector2::new(200.0, 0.0));
+        row.add_child(label.upcast());
+        row.add_child(control.upcast());
+
+        panel.add_child(row.upcast());
+    }
+
+    fn add_button(&self, panel: &mut Gd<VBoxContainer>, text: &str, method: &str) -> Gd<Button> {
+        let mut button = Button::new_alloc();
+        button.set_text(text.into());
+        button.set_custom_minimum_size(Vector2::new(240.0, 0.0));
+        button.connect("pressed".into(), self.base().callable(method));
+
+        panel.add_child(button.clone().upcast());
+        button
+    }
+}
@@ -0,0 +1,123 @@
+use godot::engine::{Area3D, IArea3D, Node3D, Timer};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Area3D)]
+pub struct Pickup {
+    #[export(enum = (Health, Ammo, Armor))]
+    kind: i32,
+    #[export(range = (1.0, 100.0))]
+    amount: i32,
+    #[export(range = (0.0, 120.0))]
+    respawn_time: f64,
+
+    #[export]
+    sound_pickup: GString,
+
+    respawn_timer: OnReady<Gd<Timer>>,
+
+    time: f64,
+    origin: Vector3,
+    collected: bool,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for Pickup {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            kind: 0,
+            amount: 25,
+            respawn_time: 0.0,
+
+            sound_pickup: GString::from("sounds/weapon_change.ogg"),
+
+            respawn_timer: OnReady::manual(),
+
+            time: 0.0,
+            origin: Vector3::ZERO,
+            collected: false,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.origin = self.base().get_position();
+
+        let mut timer = Timer::new_alloc();
+        timer.set_one_shot(true);
+        timer.connect(
+            "timeout".into(),
+            self.base().callable("_on_respawn_timeout"),
+        );
+        self.base_mut().add_child(timer.clone().upcast());
+        self.respawn_timer.init(timer);
+
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+    }
+
+    fn process(&mut self, delta: f64) {
+        self.time += delta;
+
+        let mut position = self.origin;
+        position.y += ((self.time * 2.5).cos() * 0.1) as f32;
+        self.base_mut().set_position(position);
+        self.base_mut().rotate_y(delta as f32 * 2.0);
+    }
+}
+
+#[godot_api]
+impl Pickup {
+    #[func]
+    fn _on_body_entered(&mut self, mut body: Gd<Node3D>) {
+        if self.collected {
+            return;
+        }
+
+        let method = match self.kind {
+            0 => "heal",
+            1 => "add_ammo",
+            _ => "add_armor",
+        };
+
+        if !body.has_method(method.into())
+            || !body
+                .call(method.into(), &[Variant::from(self.amount)])
+                .to::<bool>()
+        {
+            return;
+        }
+
+        let sound_pickup = self.sound_pickup.to_string();
+        self.play_sound(&sound_pickup);
+
+        if self.respawn_time <= 0.0 {
+            self.base_mut().queue_free();
+            return;
+        }
+
+        self.collected = true;
+        self.base_mut().hide();
+        self.base_mut()
+            .set_deferred("monitoring".into(), Variant::from(false));
+
+        let respawn_time = self.respawn_time;
+        self.respawn_timer.start_ex().time_sec(respawn_time).done();
+    }
+
+    #[func]
+    fn _on_respawn_timeout(&mut self) {
+        self.collected = false;
+        self.base_mut().show();
+        self.base_mut()
+            .set_deferred("monitoring".into(), Variant::from(true));
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+}
@@ -1,14 +1,22 @@
+use std::f32::consts::{PI, TAU};
+
+use godot::builtin::EulerOrder;
 use godot::engine::input::MouseMode;
+use godot::engine::mesh::PrimitiveType;
 use godot::engine::tween::EaseType;
-use godot::engine::utilities::{deg_to_rad, lerp, lerp_angle};
+use godot::engine::utilities::{deg_to_rad, lerp, lerp_angle, wrapf};
 use godot::engine::{
-    AnimatedSprite3D, CharacterBody3D, ICharacterBody3D, InputEvent, InputEventMouseMotion,
-    MeshInstance3D, RayCast3D, Texture2D, TextureRect, Timer, Tween,
+    AnimatedSprite3D, AudioStream, CharacterBody3D, ICharacterBody3D, ImmediateMesh, InputEvent,
+    InputEventMouseMotion, MeshInstance3D, RayCast3D, ResourceLoader, StaticBody3D, Texture2D,
+    TextureRect, Timer, Tween,
 };
 use godot::prelude::*;
 use rand::Rng;
 
+use crate::camera_shake::CameraShake;
 use crate::impact::Impact;
+use crate::settings::SettingsBase;
+use crate::surface::SurfaceSounds;
 use crate::weapon::Weapon;
 
 #[derive(GodotClass)]
@@ -18,15 +26,103 @@ pub struct Player {
     movement_speed: f32,
     #[export]
     jump_strength: f32,
+    #[export(range = (1.0, 3.0))]
+    sprint_multiplier: f32,
+    #[export(range = (0.1, 1.0))]
+    crouch_multiplier: f32,
+
+    #[export]
+    surfaces: Array<Gd<SurfaceSounds>>,
+    #[export(range = (0.5, 5.0))]
+    stride_length: f32,
+    #[export]
+    sound_footsteps: GString,
+
+    #[export(range = (0.0, 100.0))]
+    noise_footsteps: f32,
+    #[export(range = (0.0, 100.0))]
+    noise_shoot: f32,
+
+    #[export]
+    fall_damage: bool,
+    #[export(range = (0.0, 50.0))]
+    hard_landing_speed: f32,
+    #[export(range = (0.0, 50.0))]
+    fall_damage_speed: f32,
+    #[export(range = (0.0, 20.0))]
+    fall_damage_multiplier: f32,
+
+    #[export]
+    sound_land: GString,
+    #[export]
+    sound_land_hard: GString,
+    #[export]
+    sound_land_damage: GString,
+
+    #[export]
+    death_height: f32,
+
+    #[export(range = (0.0, 0.2))]
+    head_bob: f32,
+    #[export(range = (0.0, 10.0))]
+    weapon_sway: f32,
+    #[export(range = (0.0, 0.5))]
+    weapon_tilt: f32,
+    #[export(range = (0.0, 0.05))]
+    breathing: f32,
+    #[export]
+    reduced_motion: bool,
+
+    #[export(range = (0.0, 10.0))]
+    climb_speed: f32,
+    #[export(range = (0.0, 20.0))]
+    climb_push: f32,
+    #[export]
+    sound_climb: GString,
+
+    #[export(range = (0.0, 10.0))]
+    swim_speed: f32,
+    #[export(range = (0.0, 10.0))]
+    buoyancy: f32,
+    #[export(range = (0.0, 60.0))]
+    max_oxygen: f64,
+    #[export(range = (0.0, 100.0))]
+    drowning_damage: f32,
+
+    #[export]
+    max_health: i32,
+    #[export]
+    max_armor: i32,
+    #[export(range = (0.0, 1.0))]
+    armor_absorption: f32,
+    #[export(range = (0.0, 50.0))]
+    regeneration_rate: f32,
+    #[export(range = (0.0, 10.0))]
+    regeneration_delay: f64,
+
+    #[export]
+    aim_assist: bool,
+    #[export(range = (0.0, 30.0))]
+    aim_assist_angle: f32,
+    #[export(range = (0.0, 100.0))]
+    aim_assist_range: f32,
+    #[export(range = (0.0, 1.0))]
+    aim_assist_slowdown: f32,
+    #[export(range = (0.0, 10.0))]
+    aim_assist_strength: f32,
+    #[export]
+    aim_assist_debug: bool,
 
     #[export]
     weapons: Array<Gd<Weapon>>,
 
     weapon: Option<Gd<Weapon>>,
     weapon_index: usize,
+    ammo: Vec<i32>,
 
     mouse_sensitivity: f32,
     gamepad_sensitivity: f32,
+    invert_y: bool,
 
     mouse_captured: bool,
 
@@ -35,9 +131,38 @@ pub struct Player {
 
     input_mouse: Vector2,
 
+    sway: Vector2,
+    strafe: f32,
+    bob_phase: f32,
+    bob_weight: f32,
+    time: f64,
+
     health: i32,
+    armor: i32,
     gravity: f32,
 
+    time_since_damage: f64,
+    regeneration_progress: f32,
+
+    dead: bool,
+
+    sprinting: bool,
+    crouching: bool,
+    surface: GString,
+    step_distance: f32,
+
+    launched: bool,
+
+    climbing: bool,
+    climb_normal: Vector3,
+    climb_distance: f32,
+
+    swimming: bool,
+    underwater: bool,
+    water_surface: f32,
+    oxygen: f64,
+    drowning: f64,
+
     previously_floored: bool,
 
     jump_single: bool,
@@ -45,14 +170,17 @@ pub struct Player {
 
     container_offset: Vector3,
 
+    aim_assist_gizmo: Option<Gd<ImmediateMesh>>,
+
     tween: Option<Gd<Tween>>,
 
     camera: OnReady<Gd<Camera3D>>,
+    shake: OnReady<Gd<CameraShake>>,
     raycast: OnReady<Gd<RayCast3D>>,
     muzzle: OnReady<Gd<AnimatedSprite3D>>,
     container: OnReady<Gd<Node3D>>,
-    sound_footsteps: OnReady<Gd<AudioStreamPlayer>>,
     blaster_cooldown: OnReady<Gd<Timer>>,
+    footsteps: OnReady<Gd<AudioStreamPlayer>>,
 
     #[export]
     crosshair: Option<Gd<TextureRect>>,
@@ -66,14 +194,64 @@ impl ICharacterBody3D for Player {
         Self {
             movement_speed: 5.0,
             jump_strength: 8.0,
+            sprint_multiplier: 1.5,
+            crouch_multiplier: 0.5,
+
+            surfaces: Array::new(),
+            stride_length: 2.0,
+            sound_footsteps: GString::from("sounds/walking.ogg"),
+
+            noise_footsteps: 8.0,
+            noise_shoot: 30.0,
+
+            fall_damage: true,
+            hard_landing_speed: 11.0,
+            fall_damage_speed: 15.0,
+            fall_damage_multiplier: 4.0,
+
+            sound_land: GString::from("sounds/land.ogg"),
+            sound_land_hard: GString::from("sounds/land.ogg"),
+            sound_land_damage: GString::from("sounds/land.ogg"),
+
+            death_height: -10.0,
+
+            head_bob: 0.04,
+            weapon_sway: 3.0,
+            weapon_tilt: 0.08,
+            breathing: 0.01,
+            reduced_motion: false,
+
+            climb_speed: 3.0,
+            climb_push: 4.0,
+            sound_climb: GString::new(),
+
+            swim_speed: 3.0,
+            buoyancy: 1.0,
+            max_oxygen: 10.0,
+            drowning_damage: 10.0,
+
+            max_health: 100,
+            max_armor: 100,
+            armor_absorption: 0.66,
+            regeneration_rate: 0.0,
+            regeneration_delay: 4.0,
+
+            aim_assist: true,
+            aim_assist_angle: 8.0,
+            aim_assist_range: 30.0,
+            aim_assist_slowdown: 0.5,
+            aim_assist_strength: 1.5,
+            aim_assist_debug: false,
 
             weapons: Array::new(),
 
             weapon: None,
             weapon_index: 0,
+            ammo: Vec::new(),
 
             mouse_sensitivity: 700.0,
             gamepad_sensitivity: 0.075,
+            invert_y: false,
 
             mouse_captured: true,
 
@@ -82,23 +260,55 @@ impl ICharacterBody3D for Player {
 
             input_mouse: Vector2::ZERO,
 
+            sway: Vector2::ZERO,
+            strafe: 0.0,
+            bob_phase: 0.0,
+            bob_weight: 0.0,
+            time: 0.0,
+
             health: 100,
+            armor: 0,
             gravity: 0.0,
 
+            time_since_damage: 0.0,
+            regeneration_progress: 0.0,
+
+            dead: false,
+
+            sprinting: false,
+            crouching: false,
+            surface: GString::new(),
+            step_distance: 0.0,
+
+            launched: false,
+
+            climbing: false,
+            climb_normal: Vector3::ZERO,
+            climb_distance: 0.0,
+
+            swimming: false,
+            underwater: false,
+            water_surface: 0.0,
+            oxygen: 10.0,
+            drowning: 0.0,
+
             previously_floored: false,
 
             jump_single: true,
             jump_double: true,
             container_offset: Vector3::new(1.2, -1.1, -2.75),
 
+            aim_assist_gizmo: None,
+
             tween: None,
 
             camera: OnReady::manual(),
+            shake: OnReady::manual(),
             raycast: OnReady::manual(),
             muzzle: OnReady::manual(),
             container: OnReady::manual(),
-            sound_footsteps: OnReady::manual(),
             blaster_cooldown: OnReady::manual(),
+            footsteps: OnReady::manual(),
 
             crosshair: None,
 
@@ -108,6 +318,8 @@ impl ICharacterBody3D for Player {
 
     fn ready(&mut self) {
         self.camera.init(self.base().get_node_as("Head/Camera"));
+        self.shake
+            .init(self.base().get_node_as("Head/Camera/Shake"));
         self.raycast
             .init(self.base().get_node_as("Head/Camera/RayCast"));
         self.muzzle.init(
@@ -118,26 +330,73 @@ impl ICharacterBody3D for Player {
             self.base()
                 .get_node_as("Head/Camera/SubViewportContainer/SubViewport/CameraItem/Container"),
         );
-        self.sound_footsteps
-            .init(self.base().get_node_as("SoundFootsteps"));
         self.blaster_cooldown
             .init(self.base().get_node_as("Cooldown"));
 
+        // Kept out of the Audio pool, a looping walk clip never finishes and
+        // would hold on to a pooled player for good
+        let mut footsteps = AudioStreamPlayer::new_alloc();
+        footsteps.set_volume_db(-10.0);
+        footsteps.set_bus("SFX".into());
+        self.base_mut().add_child(footsteps.clone().upcast());
+        self.footsteps.init(footsteps);
+
         Input::singleton().set_mouse_mode(MouseMode::CAPTURED);
 
+        if self.aim_assist_debug {
+            let gizmo = ImmediateMesh::new_gd();
+            let mut instance = MeshInstance3D::new_alloc();
+            instance.set_as_top_level(true);
+            instance.set_mesh(gizmo.clone().upcast());
+            self.base_mut().add_child(instance.upcast());
+            self.aim_assist_gizmo = Some(gizmo);
+        }
+
+        let mut settings = self.base().get_node_as::<SettingsBase>("/root/Settings");
+        settings.connect(
+            "settings_changed".into(),
+            self.base().callable("_on_settings_changed"),
+        );
+        self.apply_settings(&settings.bind());
+
+        self.health = self.max_health;
+        self.ammo = self
+            .weapons
+            .iter_shared()
+            .map(|x| x.get("max_ammo".into()).to::<i32>())
+            .collect();
+
         self.weapon = Some(self.weapons.get(self.weapon_index));
         self.initiate_change_weapon(self.weapon_index);
+
+        let (health, armor) = (self.health, self.armor);
+        self.base_mut()
+            .emit_signal("health_updated".into(), &[Variant::from(health)]);
+        self.base_mut()
+            .emit_signal("armor_updated".into(), &[Variant::from(armor)]);
     }
 
     fn physics_process(&mut self, delta: f64) {
+        if self.dead {
+            return;
+        }
+
         self.handle_controls(delta);
-        self.handle_gravity(delta);
+        if self.swimming {
+            self.handle_swimming(delta);
+        } else if !self.climbing {
+            self.handle_gravity(delta);
+        }
+        self.handle_regeneration(delta);
+
+        // Launched bodies keep most of their momentum until they land
+        let control = if self.launched { 1.0 } else { 10.0 };
 
         self.movement_velocity = self.base().get_transform().basis * self.movement_velocity;
         let mut applied_velocity = self
             .base()
             .get_velocity()
-            .lerp(self.movement_velocity, (delta * 10.0) as f32);
+            .lerp(self.movement_velocity, (delta * control) as f32);
         applied_velocity.y = -self.gravity;
 
         self.base_mut().set_velocity(applied_velocity);
@@ -164,41 +423,98 @@ impl ICharacterBody3D for Player {
         ) as f32;
         self.base_mut().set_rotation(rotation);
 
+        self.time += delta;
+        let bob = self.head_bob_offset();
+        let breathing = if self.reduced_motion {
+            0.0
+        } else {
+            (self.time * 1.5).sin() as f32 * self.breathing
+        };
+
         let position = lerp(
             Variant::from(self.container.get_position()),
-            Variant::from(self.container_offset - (applied_velocity / 30.0)),
+            Variant::from(
+                self.container_offset - (applied_velocity / 30.0)
+                    + Vector3::new(bob.x, bob.y + breathing, 0.0) * 0.5,
+            ),
             Variant::from(delta * 10.0),
         );
         self.container.set_position(position.to::<Vector3>());
 
-        self.sound_footsteps.set_stream_paused(true);
+        // The weapon lags behind mouse look and leans into strafing
+        self.sway = self.sway.lerp(Vector2::ZERO, (delta * 8.0).min(1.0) as f32);
+        let sway = self.sway * self.weapon_sway * self.sway_multiplier();
+        let rotation = Vector3::new(
+            sway.y.clamp(-0.3, 0.3),
+            sway.x.clamp(-0.3, 0.3),
+            -self.strafe * self.weapon_tilt * self.sway_multiplier(),
+        );
+        let rotation = self
+            .container
+            .get_rotation()
+            .lerp(rotation, (delta * 10.0) as f32);
+        self.container.set_rotation(rotation);
+
+        if self.climbing {
+            self.climb_distance += self.gravity.abs() * delta as f32;
+            if self.climb_distance > 0.6 {
+                self.climb_distance = 0.0;
+                let sound_climb = self.sound_climb.to_string();
+                self.play_sound(&sound_climb);
+            }
+        } else if self.base().is_on_floor() && !self.swimming {
+            self.detect_surface();
 
-        if self.base().is_on_floor() {
             let velocity = self.base().get_velocity();
-            if velocity.x.abs() > 1.0 || velocity.z.abs() > 1.0 {
-                self.sound_footsteps.set_stream_paused(false);
+            let speed = Vector2::new(velocity.x, velocity.z).length();
+            if speed > 1.0 {
+                self.bob_phase = (self.bob_phase + speed * delta as f32 / self.stride() * PI) % TAU;
+                self.step_distance += speed * delta as f32;
+                if self.step_distance >= self.stride() {
+                    self.step_distance = 0.0;
+                    self.play_footstep();
+                }
             }
         }
 
+        let velocity = self.base().get_velocity();
+        let bobbing = self.base().is_on_floor()
+            && !self.swimming
+            && !self.climbing
+            && Vector2::new(velocity.x, velocity.z).length() > 1.0;
+        let bob_weight = if bobbing { 1.0 } else { 0.0 };
+
+        // A looping walk clip keeps going only while the player does
+        if !bobbing && self.footsteps.is_playing() {
+            self.footsteps.stop();
+        }
+
+        self.bob_weight += (bob_weight - self.bob_weight) * (delta as f32 * 5.0).min(1.0);
+
         let mut camera_position = self.camera.get_position();
+        camera_position.x = bob.x;
         camera_position.y = lerp(
             Variant::from(camera_position.y),
-            Variant::from(0.0f32),
+            Variant::from(bob.y),
             Variant::from((delta * 5.0) as f32),
         )
         .to::<f32>();
 
-        if self.base().is_on_floor() && self.gravity > 1.0 && !self.previously_floored {
-            self.play_sound("sounds/land.ogg");
-            camera_position.y = -0.1;
+        if self.base().is_on_floor()
+            && self.gravity > 1.0
+            && !self.previously_floored
+            && !self.climbing
+            && !self.swimming
+        {
+            camera_position.y = self.land(self.gravity);
         }
         self.camera.set_position(camera_position);
 
         self.previously_floored = self.base().is_on_floor();
 
         let position = self.base().get_position();
-        if position.y < -10.0 {
-            self.base_mut().get_tree().unwrap().reload_current_scene();
+        if position.y < self.death_height {
+            self.die();
         }
     }
 
@@ -207,9 +523,10 @@ impl ICharacterBody3D for Player {
             if self.mouse_captured {
                 let relative = event.get_relative();
                 self.input_mouse = relative / self.mouse_sensitivity;
+                self.sway += relative / self.mouse_sensitivity;
 
                 self.rotation_target.y -= relative.x / self.mouse_sensitivity;
-                self.rotation_target.x -= relative.y / self.mouse_sensitivity;
+                self.rotation_target.x -= relative.y / self.mouse_sensitivity * self.look_y();
             }
         }
     }
@@ -217,7 +534,7 @@ impl ICharacterBody3D for Player {
 
 #[godot_api]
 impl Player {
-    pub fn handle_controls(&mut self, _delta: f64) {
+    pub fn handle_controls(&mut self, delta: f64) {
         let mut input = Input::singleton();
 
         if input.is_action_just_pressed("mouse_capture".into()) {
@@ -225,13 +542,6 @@ impl Player {
             self.mouse_captured = true;
         }
 
-        if input.is_action_just_pressed("mouse_capture_exit".into()) {
-            input.set_mouse_mode(MouseMode::VISIBLE);
-            self.mouse_captured = false;
-
-            self.input_mouse = Vector2::ZERO;
-        }
-
         let input_vector = input.get_vector(
             "move_left".into(),
             "move_right".into(),
@@ -239,8 +549,27 @@ impl Player {
             "move_back".into(),
         );
 
-        self.movement_velocity =
-            Vector3::new(input_vector.x, 0.0, input_vector.y).normalized() * self.movement_speed;
+        self.strafe = input_vector.x;
+
+        if self.climbing {
+            self.movement_velocity =
+                Vector3::new(input_vector.x, 0.0, 0.0) * self.movement_speed * 0.5;
+            self.gravity = input_vector.y * self.climb_speed;
+        } else if self.swimming {
+            let pitch = Basis::from_euler(
+                EulerOrder::YXZ,
+                Vector3::new(self.rotation_target.x, 0.0, 0.0),
+            );
+            self.movement_velocity = pitch
+                * Vector3::new(input_vector.x, 0.0, input_vector.y).normalized()
+                * self.swim_speed;
+        } else {
+            self.sprinting = input.is_action_pressed("sprint".into());
+            self.crouching = !self.sprinting && input.is_action_pressed("crouch".into());
+            self.movement_velocity = Vector3::new(input_vector.x, 0.0, input_vector.y).normalized()
+                * self.movement_speed
+                * self.speed_multiplier();
+        }
 
         let rotation_input = input.get_vector(
             "camera_right".into(),
@@ -248,9 +577,25 @@ impl Player {
             "camera_down".into(),
             "camera_up".into(),
         );
-        self.rotation_target -= Vector3::new(-rotation_input.y, -rotation_input.x, 0.0)
+        let mut look = Vector3::new(-rotation_input.y * self.look_y(), -rotation_input.x, 0.0)
             .limit_length(Some(1.0))
             * self.gamepad_sensitivity;
+
+        // Aim assist only ever touches stick input, mouse look is left alone
+        let assisting = look != Vector3::ZERO || self.aim_assist_gizmo.is_some();
+        let target = if self.aim_assist && assisting {
+            self.aim_assist_target()
+        } else {
+            None
+        };
+        if let Some((direction, closeness)) = target {
+            if look != Vector3::ZERO {
+                look = self.apply_aim_assist(look, direction, closeness, delta);
+            }
+        }
+        self.draw_aim_assist(target.map(|(direction, _)| direction));
+
+        self.rotation_target -= look;
         self.rotation_target.x = self
             .rotation_target
             .x
@@ -258,9 +603,15 @@ impl Player {
 
         self.action_shoot();
 
-        if input.is_action_just_pressed("jump".into()) {
+        if input.is_action_just_pressed("jump".into()) && self.climbing {
+            self.action_climb_detach();
+        } else if input.is_action_just_pressed("jump".into()) && !self.swimming {
             if self.jump_single || self.jump_double {
-                self.play_sound("sounds/jump_a.ogg, sounds/jump_b.ogg, sounds/jump_c.ogg");
+                let sound_jump = self.surface_sound(
+                    "jump",
+                    "sounds/jump_a.ogg, sounds/jump_b.ogg, sounds/jump_c.ogg",
+                );
+                self.play_sound(&sound_jump);
             }
 
             if self.jump_double {
@@ -280,10 +631,63 @@ impl Player {
         self.gravity += 20.0 * delta as f32;
         if self.gravity > 0.0 && self.base().is_on_floor() {
             self.jump_single = true;
+            self.launched = false;
             self.gravity = 0.0;
         }
     }
 
+    fn handle_swimming(&mut self, delta: f64) {
+        let head_height = self.camera.get_global_position().y;
+
+        let mut vertical = self.movement_velocity.y;
+        if Input::singleton().is_action_pressed("jump".into()) {
+            vertical += self.swim_speed;
+        }
+        if vertical == 0.0 && head_height < self.water_surface {
+            vertical = self.buoyancy;
+        }
+        self.gravity += (-vertical - self.gravity) * (delta as f32 * 5.0).min(1.0);
+
+        let underwater = head_height < self.water_surface;
+        if underwater != self.underwater {
+            self.underwater = underwater;
+            self.set_underwater_audio(underwater);
+        }
+
+        if !underwater {
+            self.oxygen = self.max_oxygen;
+            self.drowning = 0.0;
+            return;
+        }
+
+        self.oxygen -= delta;
+        if self.oxygen <= 0.0 {
+            self.drowning += delta;
+            if self.drowning >= 1.0 {
+                self.drowning -= 1.0;
+                self.damage(self.drowning_damage);
+            }
+        }
+    }
+
+    fn handle_regeneration(&mut self, delta: f64) {
+        self.time_since_damage += delta;
+        if self.regeneration_rate <= 0.0
+            || self.time_since_damage < self.regeneration_delay
+            || self.health >= self.max_health
+        {
+            self.regeneration_progress = 0.0;
+            return;
+        }
+
+        self.regeneration_progress += self.regeneration_rate * delta as f32;
+        if self.regeneration_progress >= 1.0 {
+            let amount = self.regeneration_progress as i32;
+            self.regeneration_progress -= amount as f32;
+            self.heal(amount);
+        }
+    }
+
     fn action_jump(&mut self) {
         self.gravity = -self.jump_strength;
 
@@ -291,6 +695,326 @@ impl Player {
         self.jump_double = true;
     }
 
+    fn action_climb_detach(&mut self) {
+        self.play_sound("sounds/jump_a.ogg, sounds/jump_b.ogg, sounds/jump_c.ogg");
+
+        self.climbing = false;
+        self.gravity = -self.jump_strength * 0.5;
+        self.jump_single = false;
+        self.jump_double = true;
+
+        let velocity = self.base().get_velocity() + self.climb_normal * self.climb_push;
+        self.base_mut().set_velocity(velocity);
+    }
+
+    // Finds the visible enemy nearest to the crosshair within the assist cone,
+    // returning the direction to it and how close it is to the center (0 to 1)
+    fn aim_assist_target(&mut self) -> Option<(Vector3, f32)> {
+        let cone = deg_to_rad(self.aim_assist_angle as f64) as f32;
+        if cone <= 0.0 {
+            return None;
+        }
+
+        let origin = self.camera.get_global_position();
+        let forward = -self.camera.get_global_transform().basis.col_c();
+
+        let mut best: Option<(Vector3, f32)> = None;
+        let enemies = self
+            .base()
+            .get_tree()
+            .unwrap()
+            .get_nodes_in_group("enemy".into());
+        for enemy in enemies.iter_shared() {
+            let Ok(enemy) = enemy.try_cast::<Node3D>() else {
+                continue;
+            };
+
+            let offset = enemy.get_global_position() - origin;
+            let distance = offset.length();
+            if distance < 0.01 || distance > self.aim_assist_range {
+                continue;
+            }
+
+            let angle = forward.angle_to(offset);
+            if angle > cone || best.is_some_and(|(_, best)| best <= angle) {
+                continue;
+            }
+
+            if self.is_target_visible(&enemy) {
+                best = Some((offset / distance, angle));
+            }
+        }
+
+        best.map(|(direction, angle)| (direction, 1.0 - angle / cone))
+    }
+
+    // Reuses the shooting raycast, restoring it afterwards
+    fn is_target_visible(&mut self, target: &Gd<Node3D>) -> bool {
+        let previous = self.raycast.get_target_position();
+        let local = self.raycast.to_local(target.get_global_position());
+        self.raycast.set_target_position(local);
+        self.raycast.force_raycast_update();
+
+        let visible = self
+            .raycast
+            .get_collider()
+            .is_some_and(|x| x.instance_id() == target.instance_id());
+
+        self.raycast.set_target_position(previous);
+        visible
+    }
+
+    // Slows the stick down near a target and, while the player is already
+    // turning toward it, gently pulls the aim onto it
+    fn apply_aim_assist(
+        &self,
+        look: Vector3,
+        direction: Vector3,
+        closeness: f32,
+        delta: f64,
+    ) -> Vector3 {
+        let mut look = look * (1.0 - self.aim_assist_slowdown * closeness);
+
+        let yaw = (-direction.x).atan2(-direction.z);
+        let pitch = direction.y.clamp(-1.0, 1.0).asin();
+        let error = Vector3::new(
+            pitch - self.rotation_target.x,
+            wrapf((yaw - self.rotation_target.y) as f64, -PI as f64, PI as f64) as f32,
+            0.0,
+        );
+
+        // `look` is subtracted from the rotation target
+        if (-look).dot(error) > 0.0 {
+            let pull = self.aim_assist_strength * closeness * delta as f32;
+            look -= error.limit_length(Some(pull));
+        }
+
+        look
+    }
+
+    // Draws the assist cone as a ring in front of the camera and marks the target
+    fn draw_aim_assist(&mut self, target: Option<Vector3>) {
+        let Some(mut gizmo) = self.aim_assist_gizmo.clone() else {
+            return;
+        };
+
+        let transform = self.camera.get_global_transform();
+        let radius = 2.0 * deg_to_rad(self.aim_assist_angle as f64).tan() as f32;
+
+        gizmo.clear_surfaces();
+        gizmo.surface_begin(PrimitiveType::LINES);
+
+        for i in 0..32 {
+            for j in [i, i + 1] {
+                let angle = j as f32 / 32.0 * TAU;
+                let point = Vector3::new(angle.cos() * radius, angle.sin() * radius, -2.0);
+                gizmo.surface_add_vertex(transform * point);
+            }
+        }
+
+        if let Some(direction) = target {
+            let center = transform.origin + direction * 2.0;
+            for axis in [Vector3::RIGHT, Vector3::UP, Vector3::BACK] {
+                let axis = transform.basis * axis * 0.05;
+                gizmo.surface_add_vertex(center - axis);
+                gizmo.surface_add_vertex(center + axis);
+            }
+        }
+
+        gizmo.surface_end();
+    }
+
+    fn look_y(&self) -> f32 {
+        if self.invert_y {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    fn head_bob_offset(&self) -> Vector2 {
+        if self.reduced_motion {
+            return Vector2::ZERO;
+        }
+
+        Vector2::new(self.bob_phase.sin() * 0.5, (self.bob_phase * 2.0).sin())
+            * self.head_bob
+            * self.bob_weight
+    }
+
+    fn sway_multiplier(&self) -> f32 {
+        let weapon_sway = self
+            .weapon
+            .as_ref()
+            .map(|x| x.get("sway".into()).to::<f32>())
+            .unwrap_or(1.0);
+
+        if self.reduced_motion {
+            weapon_sway * 0.25
+        } else {
+            weapon_sway
+        }
+    }
+
+    fn speed_multiplier(&self) -> f32 {
+        if self.sprinting {
+            self.sprint_multiplier
+        } else if self.crouching {
+            self.crouch_multiplier
+        } else {
+            1.0
+        }
+    }
+
+    fn stride(&self) -> f32 {
+        self.stride_length * self.speed_multiplier().sqrt()
+    }
+
+    fn play_footstep(&mut self) {
+        let sound_footsteps = self.sound_footsteps.to_string();
+        let mut sound = self.surface_sound("footsteps", &sound_footsteps);
+        if self.sprinting {
+            sound = self.surface_sound("footsteps_sprint", &sound);
+        } else if self.crouching {
+            sound = self.surface_sound("footsteps_crouch", &sound);
+        }
+        self.play_footstep_sound(&sound);
+
+        self.make_noise(self.noise_footsteps * self.speed_multiplier());
+    }
+
+    // Restarts on every stride, except for a looping clip that is already
+    // running. Like Audio, `audio_path` may list several sounds to pick from.
+    fn play_footstep_sound(&mut self, audio_path: &str) {
+        if audio_path.is_empty() {
+            return;
+        }
+
+        let sounds = audio_path.split(',').collect::<Vec<_>>();
+        let path = format!(
+            "res://{}",
+            sounds[rand::thread_rng().gen_range(0..sounds.len())].trim()
+        );
+        let Some(stream) = ResourceLoader::singleton()
+            .load(path.into())
+            .and_then(|x| x.try_cast::<AudioStream>().ok())
+        else {
+            return;
+        };
+
+        let current = self.footsteps.get_stream();
+        if self.footsteps.is_playing()
+            && current.is_some_and(|x| x.instance_id() == stream.instance_id())
+            && stream.get("loop".into()).try_to::<bool>().unwrap_or(false)
+        {
+            return;
+        }
+
+        self.footsteps.set_stream(stream);
+        self.footsteps
+            .set_pitch_scale(rand::thread_rng().gen_range(0.9..1.1));
+        self.footsteps.play();
+    }
+
+    // Lets enemies within `radius` hear the player
+    fn make_noise(&mut self, radius: f32) {
+        let position = self.base().get_position();
+        self.base_mut().emit_signal(
+            "noise_made".into(),
+            &[Variant::from(position), Variant::from(radius)],
+        );
+    }
+
+    // Finds the surface name of the floor below from the collider's "surface"
+    // metadata, its physics material name or a group matching `surfaces`
+    fn detect_surface(&mut self) {
+        for index in 0..self.base().get_slide_collision_count() {
+            let Some(collision) = self.base_mut().get_slide_collision(index) else {
+                continue;
+            };
+            if collision.get_normal().y < 0.7 {
+                continue;
+            }
+            let Some(collider) = collision.get_collider() else {
+                continue;
+            };
+
+            if collider.has_meta("surface".into()) {
+                self.surface = collider.get_meta("surface".into()).to::<GString>();
+                return;
+            }
+
+            if let Ok(body) = collider.clone().try_cast::<StaticBody3D>() {
+                if let Some(material) = body.get_physics_material_override() {
+                    if !material.get_name().is_empty() {
+                        self.surface = material.get_name();
+                        return;
+                    }
+                }
+            }
+
+            if let Ok(node) = collider.try_cast::<Node>() {
+                for surface in self.surfaces.iter_shared() {
+                    let name = surface.get("surface".into()).to::<GString>();
+                    if node.is_in_group(StringName::from(&name)) {
+                        self.surface = name;
+                        return;
+                    }
+                }
+            }
+
+            self.surface = GString::new();
+            return;
+        }
+    }
+
+    // Looks up `property` of the sound table for the current surface, falling
+    // back to `default` when the surface has no entry for it
+    fn surface_sound(&self, property: &str, default: &str) -> String {
+        for surface in self.surfaces.iter_shared() {
+            if surface.get("surface".into()).to::<GString>() != self.surface {
+                continue;
+            }
+
+            let sound = surface.get(property.into()).to::<GString>();
+            if !sound.is_empty() {
+                return sound.to_string();
+            }
+        }
+
+        default.to_string()
+    }
+
+    // Plays the landing sound matching the impact speed, applies fall damage
+    // when enabled and returns the camera dip for the landing.
+    fn land(&mut self, impact_speed: f32) -> f32 {
+        if impact_speed >= self.fall_damage_speed {
+            let sound_land = self.surface_sound("land_damage", &self.sound_land_damage.to_string());
+            self.play_sound(&sound_land);
+
+            if self.fall_damage {
+                let amount = (impact_speed - self.fall_damage_speed) * self.fall_damage_multiplier;
+                self.damage(amount);
+            }
+
+            self.add_trauma(0.6);
+
+            -0.3
+        } else if impact_speed >= self.hard_landing_speed {
+            let sound_land = self.surface_sound("land_hard", &self.sound_land_hard.to_string());
+            self.play_sound(&sound_land);
+
+            self.add_trauma(0.3);
+
+            -0.2
+        } else {
+            let sound_land = self.surface_sound("land", &self.sound_land.to_string());
+            self.play_sound(&sound_land);
+
+            -0.1
+        }
+    }
+
     fn action_shoot(&mut self) {
         let input = Input::singleton();
         if input.is_action_pressed("shoot".into()) {
@@ -298,6 +1022,15 @@ impl Player {
                 return;
             }
 
+            if self.max_ammo() > 0 {
+                if self.ammo[self.weapon_index] <= 0 {
+                    return;
+                }
+
+                self.ammo[self.weapon_index] -= 1;
+                self.emit_ammo_updated();
+            }
+
             if let Some(sound_shoot) = self
                 .weapon
                 .as_mut()
@@ -313,6 +1046,8 @@ impl Player {
             let mut camera_rotation = self.camera.get_rotation();
             camera_rotation.x += 0.025;
             self.camera.set_rotation(camera_rotation);
+            self.add_trauma(0.1);
+            self.make_noise(self.noise_shoot);
             self.movement_velocity += Vector3::new(
                 0.0,
                 0.0,
@@ -377,7 +1112,23 @@ impl Player {
 
                 let mut collider = self.raycast.get_collider().unwrap();
 
-                if collider.has_method("damage".into()) {
+                // Enemies want to know who shot them and with what
+                if collider.has_method("damage_by".into()) {
+                    let damage = self
+                        .weapon
+                        .as_ref()
+                        .unwrap()
+                        .get("damage".into())
+                        .to::<f32>();
+                    collider.call(
+                        "damage_by".into(),
+                        &[
+                            Variant::from(damage),
+                            Variant::from(self.base().clone().upcast::<Node3D>()),
+                            Variant::from(self.weapon.clone().unwrap()),
+                        ],
+                    );
+                } else if collider.has_method("damage".into()) {
                     collider.call(
                         "damage".into(),
                         &[Variant::from(
@@ -415,6 +1166,23 @@ impl Player {
         }
     }
 
+    fn max_ammo(&self) -> i32 {
+        self.weapons
+            .get(self.weapon_index)
+            .get("max_ammo".into())
+            .to::<i32>()
+    }
+
+    fn emit_ammo_updated(&mut self) {
+        let ammo = if self.max_ammo() > 0 {
+            self.ammo[self.weapon_index]
+        } else {
+            -1
+        };
+        self.base_mut()
+            .emit_signal("ammo_updated".into(), &[Variant::from(ammo)]);
+    }
+
     fn action_weapon_toggle(&mut self) {
         let input = Input::singleton();
         if input.is_action_just_pressed("weapon_toggle".into()) {
@@ -443,6 +1211,7 @@ impl Player {
     #[func]
     fn change_weapon(&mut self) {
         self.weapon = Some(self.weapons.get(self.weapon_index));
+        self.emit_ammo_updated();
 
         for n in self.container.get_children().iter_shared() {
             self.container.remove_child(n);
@@ -506,21 +1275,233 @@ impl Player {
 
     #[func]
     fn damage(&mut self, amount: f32) {
-        self.health -= amount as i32;
+        if self.dead {
+            return;
+        }
+
+        self.time_since_damage = 0.0;
+
+        let amount = amount as i32;
+        let absorbed = ((amount as f32 * self.armor_absorption) as i32).min(self.armor);
+        if absorbed > 0 {
+            self.armor -= absorbed;
+            let armor = self.armor;
+            self.base_mut()
+                .emit_signal("armor_updated".into(), &[Variant::from(armor)]);
+        }
+
+        self.health -= amount - absorbed;
+        self.add_trauma(amount as f32 / 40.0);
         let health = self.health;
         self.base_mut()
             .emit_signal("health_updated".into(), &[Variant::from(health)]);
 
         if self.health < 0 {
+            self.die();
+        }
+    }
+
+    #[func]
+    fn heal(&mut self, amount: i32) -> bool {
+        if self.dead || self.health >= self.max_health {
+            return false;
+        }
+
+        self.health = (self.health + amount).min(self.max_health);
+        let health = self.health;
+        self.base_mut()
+            .emit_signal("health_updated".into(), &[Variant::from(health)]);
+
+        true
+    }
+
+    #[func]
+    fn add_armor(&mut self, amount: i32) -> bool {
+        if self.dead || self.armor >= self.max_armor {
+            return false;
+        }
+
+        self.armor = (self.armor + amount).min(self.max_armor);
+        let armor = self.armor;
+        self.base_mut()
+            .emit_signal("armor_updated".into(), &[Variant::from(armor)]);
+
+        true
+    }
+
+    #[func]
+    fn add_ammo(&mut self, amount: i32) -> bool {
+        if self.dead {
+            return false;
+        }
+
+        // Tops up the held weapon, or else the first one that still has room
+        let max_ammo: Vec<i32> = self
+            .weapons
+            .iter_shared()
+            .map(|x| x.get("max_ammo".into()).to::<i32>())
+            .collect();
+        let has_room = |index: usize| max_ammo[index] > 0 && self.ammo[index] < max_ammo[index];
+        let Some(index) = Some(self.weapon_index)
+            .filter(|x| has_room(*x))
+            .or_else(|| (0..max_ammo.len()).find(|x| has_room(*x)))
+        else {
+            return false;
+        };
+
+        self.ammo[index] = (self.ammo[index] + amount).min(max_ammo[index]);
+        self.emit_ammo_updated();
+
+        true
+    }
+
+    #[func]
+    fn capture_mouse(&mut self) {
+        Input::singleton().set_mouse_mode(MouseMode::CAPTURED);
+        self.mouse_captured = true;
+    }
+
+    #[func]
+    fn add_trauma(&mut self, amount: f32) {
+        self.shake.bind_mut().add_trauma(amount);
+    }
+
+    #[func]
+    fn launch(&mut self, velocity: Vector3) {
+        self.launched = true;
+        self.climbing = false;
+        self.gravity = -velocity.y;
+
+        self.jump_single = false;
+        self.jump_double = true;
+
+        self.base_mut().set_velocity(velocity);
+    }
+
+    #[func]
+    fn enter_climb(&mut self, normal: Vector3) {
+        self.climbing = true;
+        self.climb_normal = normal;
+        self.climb_distance = 0.0;
+        self.gravity = 0.0;
+    }
+
+    #[func]
+    fn exit_climb(&mut self) {
+        self.climbing = false;
+    }
+
+    #[func]
+    fn enter_water(&mut self, surface: f32) {
+        self.swimming = true;
+        self.launched = false;
+        self.climbing = false;
+        self.water_surface = surface;
+        self.oxygen = self.max_oxygen;
+        self.drowning = 0.0;
+    }
+
+    #[func]
+    fn exit_water(&mut self) {
+        self.swimming = false;
+        if self.underwater {
+            self.underwater = false;
+            self.set_underwater_audio(false);
+        }
+    }
+
+    fn die(&mut self) {
+        self.dead = true;
+
+        // Without a respawn manager listening, fall back to restarting the level
+        if self
+            .base()
+            .get_signal_connection_list("player_died".into())
+            .is_empty()
+        {
             self.base().get_tree().unwrap().reload_current_scene();
+            return;
         }
+
+        self.base_mut().emit_signal("player_died".into(), &[]);
+    }
+
+    #[func]
+    fn respawn(&mut self, transform: Transform3D) {
+        self.dead = false;
+        self.launched = false;
+        self.climbing = false;
+        self.exit_water();
+        self.health = self.max_health;
+        self.armor = 0;
+        self.time_since_damage = 0.0;
+        self.ammo = self
+            .weapons
+            .iter_shared()
+            .map(|x| x.get("max_ammo".into()).to::<i32>())
+            .collect();
+        self.gravity = 0.0;
+        self.movement_velocity = Vector3::ZERO;
+        self.previously_floored = false;
+
+        self.rotation_target = Vector3::new(0.0, transform.basis.to_euler(EulerOrder::YXZ).y, 0.0);
+        self.base_mut().set_velocity(Vector3::ZERO);
+        self.base_mut().set_global_position(transform.origin);
+        let rotation = self.rotation_target;
+        self.base_mut().set_rotation(rotation);
+
+        let health = self.health;
+        self.base_mut()
+            .emit_signal("health_updated".into(), &[Variant::from(health)]);
+        self.base_mut()
+            .emit_signal("armor_updated".into(), &[Variant::from(0)]);
+        self.emit_ammo_updated();
+    }
+
+    #[func]
+    fn _on_settings_changed(&mut self) {
+        let settings = self.base().get_node_as::<SettingsBase>("/root/Settings");
+        self.apply_settings(&settings.bind());
+    }
+
+    fn apply_settings(&mut self, settings: &SettingsBase) {
+        self.mouse_sensitivity = settings.mouse_sensitivity();
+        self.gamepad_sensitivity = settings.gamepad_sensitivity();
+        self.invert_y = settings.invert_y();
+        self.reduced_motion = settings.reduced_motion();
+
+        self.camera.set_fov(settings.fov());
+        self.shake
+            .set("shake_scale".into(), Variant::from(settings.shake_scale()));
     }
 
     fn play_sound(&self, audio_path: &str) {
+        // Optional sounds are left empty rather than pointed at a placeholder
+        if audio_path.is_empty() {
+            return;
+        }
+
         let mut audio = self.base().get_node_as::<Node>("/root/Audio");
         audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
     }
 
+    fn set_underwater_audio(&self, underwater: bool) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("set_underwater".into(), &[Variant::from(underwater)]);
+    }
+
     #[signal]
     fn health_updated(health: i32);
+
+    #[signal]
+    fn armor_updated(armor: i32);
+
+    #[signal]
+    fn ammo_updated(ammo: i32);
+
+    #[signal]
+    fn player_died();
+
+    #[signal]
+    fn noise_made(position: Vector3, radius: f32);
 }
@@ -0,0 +1,211 @@
+use godot::engine::{Area3D, IArea3D, Node3D};
+use godot::prelude::*;
+use rand::Rng;
+
+use crate::impact::Impact;
+
+#[derive(GodotClass)]
+#[class(base=Area3D)]
+pub struct Projectile {
+    #[export(range = (1.0, 100.0))]
+    speed: f32,
+    #[export]
+    damage: f32,
+    #[export(range = (0.0, 20.0))]
+    lifetime: f64,
+
+    #[export]
+    sound_destroy: GString,
+
+    velocity: Vector3,
+    shooter: Option<InstanceId>,
+    age: f64,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for Projectile {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            speed: 12.0,
+            damage: 5.0,
+            lifetime: 5.0,
+
+            sound_destroy: GString::from("sounds/enemy_hurt.ogg"),
+
+            velocity: Vector3::ZERO,
+            shooter: None,
+            age: 0.0,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+    }
+
+    fn physics_process(&mut self, delta: f64) {
+        self.age += delta;
+        if self.age > self.lifetime {
+            self.base_mut().queue_free();
+            return;
+        }
+
+        let position = self.base().get_global_position() + self.velocity * delta as f32;
+        self.base_mut().set_global_position(position);
+    }
+}
+
+#[godot_api]
+impl Projectile {
+    // Shot down by the player
+    #[func]
+    fn damage(&mut self, _amount: f32) {
+        let sound_destroy = self.sound_destroy.to_string();
+        self.play_sound(&sound_destroy);
+        self.burst();
+    }
+
+    #[func]
+    fn _on_body_entered(&mut self, mut body: Gd<Node3D>) {
+        // Passes through other enemies, only the shooter's target takes damage
+        if Some(body.instance_id()) == self.shooter || body.is_in_group("enemy".into()) {
+            return;
+        }
+
+        if body.has_method("damage".into()) {
+            body.call("damage".into(), &[Variant::from(self.damage)]);
+        }
+
+        self.burst();
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn launch(&mut self, direction: Vector3, damage: f32, shooter: InstanceId) {
+        self.velocity = direction.normalized() * self.speed;
+        self.damage = damage;
+        self.shooter = Some(shooter);
+    }
+
+    fn burst(&mut self) {
+        let impact = load::<PackedScene>("res://objects/impact.tscn");
+        let mut impact = impact.instantiate().unwrap().cast::<Impact>();
+        impact.play_ex().name("shot".into()).done();
+
+        let position = self.base().get_global_position();
+        self.base()
+            .get_tree()
+            .unwrap()
+            .get_root()
+            .unwrap()
+            .add_child(impact.clone().upcast());
+        impact.set_global_position(position);
+
+        self.base_mut().queue_free();
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+}
+
+// Spawns `scene` at `origin` and fires it at a target moving with
+// `target_velocity`, leading it and missing by up to `error` radians
+pub fn fire(
+    scene: &Gd<PackedScene>,
+    shooter: &Gd<Node3D>,
+    origin: Vector3,
+    target: Vector3,
+    target_velocity: Vector3,
+    error: f32,
+    damage: f32,
+) {
+    let Some(instance) = scene.instantiate() else {
+        return;
+    };
+    let Ok(mut projectile) = instance.try_cast::<Projectile>() else {
+        godot_error!("Projectile scene root is not a Projectile");
+        return;
+    };
+
+    let speed = projectile.bind().speed();
+    let aim = intercept(origin, target, target_velocity, speed) - origin;
+    if aim.length() < 0.01 {
+        projectile.queue_free();
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut axis = aim.cross(Vector3::UP);
+    if axis.length() < 0.01 {
+        axis = Vector3::RIGHT;
+    }
+    let axis = axis
+        .normalized()
+        .rotated(aim.normalized(), rng.gen_range(0.0..std::f32::consts::TAU));
+    let direction = if error > 0.0 {
+        aim.rotated(axis, rng.gen_range(0.0..error))
+    } else {
+        aim
+    };
+
+    shooter
+        .get_tree()
+        .unwrap()
+        .get_root()
+        .unwrap()
+        .add_child(projectile.clone().upcast());
+    projectile.set_global_position(origin);
+    projectile
+        .look_at_ex(origin + direction)
+        .use_model_front(true)
+        .done();
+    projectile
+        .bind_mut()
+        .launch(direction, damage, shooter.instance_id());
+}
+
+// Where a projectile travelling at `speed` meets a target moving at a constant
+// `target_velocity`; falls back to the target itself when it can't be caught
+pub fn intercept(
+    origin: Vector3,
+    target: Vector3,
+    target_velocity: Vector3,
+    speed: f32,
+) -> Vector3 {
+    let offset = target - origin;
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < 0.001 {
+        if b.abs() < 0.001 {
+            None
+        } else {
+            Some(-c / b)
+        }
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            None
+        } else {
+            let root = discriminant.sqrt();
+            [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+                .into_iter()
+                .filter(|x| *x > 0.0)
+                .reduce(f32::min)
+        }
+    };
+
+    match time {
+        Some(time) if time > 0.0 => target + target_velocity * time,
+        _ => target,
+    }
+}
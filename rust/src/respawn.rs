@@ -0,0 +1,112 @@
+use godot::engine::{Control, Timer};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct RespawnManager {
+    // Off when something else, like a survival spawner, handles death
+    #[export]
+    enabled: bool,
+    #[export]
+    player: Option<Gd<Node3D>>,
+    #[export]
+    death_screen: Option<Gd<Control>>,
+
+    #[export(range = (0.0, 10.0))]
+    respawn_delay: f64,
+
+    spawn_transform: Transform3D,
+    respawn_timer: OnReady<Gd<Timer>>,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for RespawnManager {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            enabled: true,
+            player: None,
+            death_screen: None,
+
+            respawn_delay: 2.0,
+
+            spawn_transform: Transform3D::IDENTITY,
+            respawn_timer: OnReady::manual(),
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let mut timer = Timer::new_alloc();
+        timer.set_one_shot(true);
+        timer.connect(
+            "timeout".into(),
+            self.base().callable("_on_respawn_timeout"),
+        );
+        self.base_mut().add_child(timer.clone().upcast());
+        self.respawn_timer.init(timer);
+
+        if let Some(mut player) = self.player.clone() {
+            self.spawn_transform = player.get_global_transform();
+            player.connect(
+                "player_died".into(),
+                self.base().callable("_on_player_died"),
+            );
+        }
+
+        for mut checkpoint in self
+            .base()
+            .get_tree()
+            .unwrap()
+            .get_nodes_in_group("checkpoint".into())
+            .iter_shared()
+        {
+            checkpoint.connect(
+                "activated".into(),
+                self.base().callable("_on_checkpoint_activated"),
+            );
+        }
+
+        if let Some(death_screen) = self.death_screen.as_mut() {
+            death_screen.hide();
+        }
+    }
+}
+
+#[godot_api]
+impl RespawnManager {
+    #[func]
+    fn _on_checkpoint_activated(&mut self, transform: Transform3D) {
+        self.spawn_transform = transform;
+    }
+
+    #[func]
+    fn _on_player_died(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(death_screen) = self.death_screen.as_mut() {
+            death_screen.show();
+        }
+
+        let respawn_delay = self.respawn_delay;
+        self.respawn_timer
+            .start_ex()
+            .time_sec(respawn_delay.max(0.01))
+            .done();
+    }
+
+    #[func]
+    fn _on_respawn_timeout(&mut self) {
+        if let Some(death_screen) = self.death_screen.as_mut() {
+            death_screen.hide();
+        }
+
+        if let Some(player) = self.player.as_mut() {
+            player.call("respawn".into(), &[Variant::from(self.spawn_transform)]);
+        }
+    }
+}
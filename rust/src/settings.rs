@@ -0,0 +1,206 @@
+use godot::engine::global::Error;
+use godot::engine::{ConfigFile, INode};
+use godot::prelude::*;
+
+const SETTINGS_PATH: &str = "user://settings.cfg";
+const SETTINGS_SECTION: &str = "settings";
+
+const SETTINGS_KEYS: [&str; 9] = [
+    "mouse_sensitivity",
+    "gamepad_sensitivity",
+    "invert_y",
+    "fov",
+    "master_volume",
+    "sfx_volume",
+    "music_volume",
+    "shake_scale",
+    "reduced_motion",
+];
+
+#[derive(GodotClass)]
+#[class(base=Node)]
+pub struct SettingsBase {
+    mouse_sensitivity: f32,
+    gamepad_sensitivity: f32,
+    invert_y: bool,
+    fov: f32,
+    master_volume: f32,
+    sfx_volume: f32,
+    music_volume: f32,
+    shake_scale: f32,
+    reduced_motion: bool,
+
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for SettingsBase {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            mouse_sensitivity: 700.0,
+            gamepad_sensitivity: 0.075,
+            invert_y: false,
+            fov: 80.0,
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            shake_scale: 1.0,
+            reduced_motion: false,
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        self.load();
+    }
+}
+
+#[godot_api]
+impl SettingsBase {
+    #[func]
+    fn load(&mut self) {
+        let mut config = ConfigFile::new_gd();
+        if config.load(SETTINGS_PATH.into()) != Error::OK {
+            return;
+        }
+
+        for key in SETTINGS_KEYS {
+            if config.has_section_key(SETTINGS_SECTION.into(), key.into()) {
+                let value = config.get_value(SETTINGS_SECTION.into(), key.into());
+                self.apply(key, value);
+            }
+        }
+
+        self.base_mut().emit_signal("settings_changed".into(), &[]);
+    }
+
+    #[func]
+    fn save(&self) {
+        let mut config = ConfigFile::new_gd();
+        for key in SETTINGS_KEYS {
+            config.set_value(SETTINGS_SECTION.into(), key.into(), self.value(key));
+        }
+
+        if config.save(SETTINGS_PATH.into()) != Error::OK {
+            godot_error!("Failed to save settings to {}", SETTINGS_PATH);
+        }
+    }
+
+    #[func]
+    fn get_setting(&self, key: GString) -> Variant {
+        self.value(&key.to_string())
+    }
+
+    #[func]
+    fn set_setting(&mut self, key: GString, value: Variant) {
+        self.apply(&key.to_string(), value);
+        self.base_mut().emit_signal("settings_changed".into(), &[]);
+    }
+
+    fn value(&self, key: &str) -> Variant {
+        match key {
+            "mouse_sensitivity" => Variant::from(self.mouse_sensitivity),
+            "gamepad_sensitivity" => Variant::from(self.gamepad_sensitivity),
+            "invert_y" => Variant::from(self.invert_y),
+            "fov" => Variant::from(self.fov),
+            "master_volume" => Variant::from(self.master_volume),
+            "sfx_volume" => Variant::from(self.sfx_volume),
+            "music_volume" => Variant::from(self.music_volume),
+            "shake_scale" => Variant::from(self.shake_scale),
+            "reduced_motion" => Variant::from(self.reduced_motion),
+            _ => Variant::nil(),
+        }
+    }
+
+    // Values out of range are clamped, values of the wrong type are ignored
+    fn apply(&mut self, key: &str, value: Variant) {
+        match key {
+            "mouse_sensitivity" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.mouse_sensitivity = value.clamp(100.0, 2000.0);
+                }
+            }
+            "gamepad_sensitivity" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.gamepad_sensitivity = value.clamp(0.01, 0.25);
+                }
+            }
+            "invert_y" => {
+                if let Ok(value) = value.try_to::<bool>() {
+                    self.invert_y = value;
+                }
+            }
+            "fov" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.fov = value.clamp(60.0, 110.0);
+                }
+            }
+            "master_volume" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.master_volume = value.clamp(0.0, 1.0);
+                }
+            }
+            "sfx_volume" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.sfx_volume = value.clamp(0.0, 1.0);
+                }
+            }
+            "music_volume" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.music_volume = value.clamp(0.0, 1.0);
+                }
+            }
+            "shake_scale" => {
+                if let Ok(value) = value.try_to::<f32>() {
+                    self.shake_scale = value.clamp(0.0, 2.0);
+                }
+            }
+            "reduced_motion" => {
+                if let Ok(value) = value.try_to::<bool>() {
+                    self.reduced_motion = value;
+                }
+            }
+            _ => godot_warn!("Unknown setting {}", key),
+        }
+    }
+
+    pub fn mouse_sensitivity(&self) -> f32 {
+        self.mouse_sensitivity
+    }
+
+    pub fn gamepad_sensitivity(&self) -> f32 {
+        self.gamepad_sensitivity
+    }
+
+    pub fn invert_y(&self) -> bool {
+        self.invert_y
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn sfx_volume(&self) -> f32 {
+        self.sfx_volume
+    }
+
+    pub fn music_volume(&self) -> f32 {
+        self.music_volume
+    }
+
+    pub fn shake_scale(&self) -> f32 {
+        self.shake_scale
+    }
+
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    #[signal]
+    fn settings_changed();
+}
@@ -0,0 +1,45 @@
+use godot::engine::Resource;
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Resource)]
+pub struct SurfaceSounds {
+    #[export]
+    surface: GString,
+
+    #[export]
+    footsteps: GString,
+    #[export]
+    footsteps_sprint: GString,
+    #[export]
+    footsteps_crouch: GString,
+    #[export]
+    land: GString,
+    #[export]
+    land_hard: GString,
+    #[export]
+    land_damage: GString,
+    #[export]
+    jump: GString,
+
+    base: Base<Resource>,
+}
+
+#[godot_api]
+impl IResource for SurfaceSounds {
+    fn init(base: Base<Resource>) -> Self {
+        Self {
+            surface: GString::new(),
+
+            footsteps: GString::new(),
+            footsteps_sprint: GString::new(),
+            footsteps_crouch: GString::new(),
+            land: GString::new(),
+            land_hard: GString::new(),
+            land_damage: GString::new(),
+            jump: GString::new(),
+
+            base,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use godot::engine::{Area3D, IArea3D, Node3D};
+use godot::prelude::*;
+
+#[derive(GodotClass)]
+#[class(base=Area3D)]
+pub struct WaterVolume {
+    #[export]
+    surface_height: f32,
+
+    #[export]
+    sound_splash: GString,
+
+    base: Base<Area3D>,
+}
+
+#[godot_api]
+impl IArea3D for WaterVolume {
+    fn init(base: Base<Area3D>) -> Self {
+        Self {
+            surface_height: 0.0,
+
+            sound_splash: GString::new(),
+
+            base,
+        }
+    }
+
+    fn ready(&mut self) {
+        let callable = self.base().callable("_on_body_entered");
+        self.base_mut().connect("body_entered".into(), callable);
+        let callable = self.base().callable("_on_body_exited");
+        self.base_mut().connect("body_exited".into(), callable);
+    }
+}
+
+#[godot_api]
+impl WaterVolume {
+    #[func]
+    fn _on_body_entered(&mut self, mut body: Gd<Node3D>) {
+        if body.has_method("enter_water".into()) {
+            let surface = self.base().get_global_position().y + self.surface_height;
+            body.call("enter_water".into(), &[Variant::from(surface)]);
+
+            let sound_splash = self.sound_splash.to_string();
+            self.play_sound(&sound_splash);
+        }
+    }
+
+    #[func]
+    fn _on_body_exited(&mut self, mut body: Gd<Node3D>) {
+        if body.has_method("exit_water".into()) {
+            body.call("exit_water".into(), &[]);
+
+            let sound_splash = self.sound_splash.to_string();
+            self.play_sound(&sound_splash);
+        }
+    }
+
+    fn play_sound(&self, audio_path: &str) {
+        if audio_path.is_empty() {
+            return;
+        }
+
+        let mut audio = self.base().get_node_as::<Node>("/root/Audio");
+        audio.call("play".into(), &[Variant::from(GString::from(audio_path))]);
+    }
+}
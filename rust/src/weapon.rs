@@ -25,6 +25,10 @@ pub struct Weapon {
     shot_count: i32,
     #[export(range = (0.0, 20.0))]
     knockback: f32,
+    #[export(range = (0.0, 500.0))]
+    max_ammo: i32,
+    #[export(range = (0.0, 3.0))]
+    sway: f32,
 
     #[export]
     sound_shoot: GString,
@@ -50,6 +54,8 @@ impl IResource for Weapon {
             spread: 0.0,
             shot_count: 1,
             knockback: 20.0,
+            max_ammo: 0,
+            sway: 1.0,
 
             sound_shoot: GString::new(),
 